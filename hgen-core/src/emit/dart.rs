@@ -0,0 +1,943 @@
+use super::rpc;
+use crate::lang::{map::OrderedHashMap, schema::*};
+
+/// Emits the models and services of a schema, with services importing the
+/// runtime from the `runtime` path, e.g. `./rpc`
+pub fn emit_schema(module_name: &str, schema: &Schema, runtime: &str) -> String {
+    let mut output = String::new();
+
+    // emit header
+    output.push_str("// AUTOGENERATED FILE - DO NOT EDIT\n");
+    output.push('\n');
+    output.push_str(&format!("import '{}.external.dart';\n", module_name));
+    output.push_str(&format!("export '{}.external.dart';\n", module_name));
+    output.push_str(
+        &schema
+            .imports
+            .iter()
+            .map(emit_import)
+            .collect::<Vec<_>>()
+            .join(""),
+    );
+    // the types implemented or caught by users of services are exported
+    if !schema.services.is_empty() {
+        output.push_str(&format!("import '{}.dart';\n", runtime));
+        output.push_str(&format!(
+            "export '{}.dart' show RequestHandler, RpcException;\n",
+            runtime
+        ));
+    }
+    output.push('\n');
+
+    // emit models
+    output.push_str(
+        &schema
+            .models
+            .iter()
+            .map(|(name, def)| emit_model(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+
+    // emit consumers
+    output.push_str(
+        &schema
+            .services
+            .iter()
+            .map(|(name, shape)| emit_consumer(name, shape))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+
+    // emit providers
+    output.push_str(
+        &schema
+            .services
+            .iter()
+            .map(|(name, def)| emit_provider(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+
+    // emit dispatchers
+    output.push_str(
+        &schema
+            .services
+            .iter()
+            .map(|(name, def)| emit_dispatcher(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+
+    // emit serialization
+    output.push_str(
+        &schema
+            .models
+            .iter()
+            .map(|(name, def)| serialize_model(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+    output.push('\n');
+
+    // emit deserialization
+    output.push_str(
+        &schema
+            .models
+            .iter()
+            .map(|(name, def)| deserialize_model(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+    output.push('\n');
+
+    output
+}
+
+/// Emits the runtime shared by the services of all modules
+pub fn emit_runtime() -> String {
+    let mut output = String::new();
+
+    output.push_str("// AUTOGENERATED FILE - DO NOT EDIT\n");
+    output.push('\n');
+    output.push_str(&rpc::expand(RPC));
+
+    output
+}
+
+/// Handler of requests used by consumers, along with helpers for decoding
+/// requests and encoding responses
+const RPC: &str = r#"abstract class RequestHandler {
+  Future<Map<String, dynamic>> request(Map<String, dynamic> request);
+}
+
+class RpcException implements Exception {
+  final String code;
+  final String message;
+
+  RpcException(this.code, this.message);
+
+  @override
+  String toString() => 'RpcException($code): $message';
+}
+
+List<dynamic> decodeParams(List<dynamic> Function() decode) {
+  try {
+    return decode();
+  } catch (error) {
+    throw RpcException('{INVALID_PARAMS}', error.toString());
+  }
+}
+
+Future<Map<String, dynamic>> respond(
+  String service,
+  Map<String, dynamic> request,
+  Future<dynamic> Function(String, Map<String, dynamic>) dispatch,
+) async {
+  try {
+    if (request['version'] != {VERSION}) {
+      throw RpcException('{UNSUPPORTED_VERSION}', 'unsupported version ${request['version']}');
+    }
+    if (request['service'] != service) {
+      throw RpcException('{UNKNOWN_SERVICE}', 'unknown service ${request['service']}');
+    }
+    var params = request['params'];
+    if (params is! Map<String, dynamic>) {
+      throw RpcException('{INVALID_PARAMS}', 'params have to be an object');
+    }
+    var result = await dispatch(request['method'] as String, params);
+    return <String, dynamic>{'version': {VERSION}, 'id': request['id'], 'result': result};
+  } catch (error) {
+    var code = error is RpcException ? error.code : '{INTERNAL}';
+    var message = error is RpcException ? error.message : error.toString();
+    return <String, dynamic>{'version': {VERSION}, 'id': request['id'], 'error': <String, dynamic>{'code': code, 'message': message}};
+  }
+}
+"#;
+
+fn emit_import(import: &Import) -> String {
+    if import.is_wildcard {
+        return format!("import '{}.dart';\n", import.module_path());
+    }
+
+    // serialization helpers are top-level functions, so they have to be shown as well
+    format!(
+        "import '{}.dart' show {};\n",
+        import.module_path(),
+        import
+            .names
+            .iter()
+            .map(|name| format!("{}, ${}ToJson, ${}FromJson", name, name, name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn emit_consumer(name: &str, service: &Service) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("class {}Consumer ", name));
+    output.push_str("{\n\n");
+    output.push_str("  final RequestHandler handler;\n");
+    output.push_str(&format!("  final String name = \"{}\";\n", name));
+    output.push_str("  int _id = 0;\n");
+    output.push('\n');
+    output.push_str(&format!("  {}Consumer(this.handler);\n", name));
+    output.push('\n');
+    output.push_str("  Future<dynamic> _request(String method, Map<String, dynamic> params) async {\n");
+    output.push_str(&format!(
+        "    var response = await handler.request(<String, dynamic>{{'version': {}, 'id': ++_id, 'service': name, 'method': method, 'params': params}});\n",
+        rpc::VERSION
+    ));
+    output.push_str("    if (response['error'] != null) {\n");
+    output.push_str("      throw RpcException(response['error']['code'], response['error']['message']);\n");
+    output.push_str("    }\n");
+    output.push_str("    return response['result'];\n");
+    output.push_str("  }\n");
+    output.push('\n');
+
+    output.push_str(
+        &service
+            .methods
+            .iter()
+            .map(|(name, def)| emit_consumer_method(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_consumer_method(name: &str, method: &Annotated<ServiceMethod>) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "  Future<{}> {}({}) async {{\n",
+        match method.inner.output {
+            None => "void".to_owned(),
+            Some(ref shape) => emit_shape(shape),
+        },
+        name,
+        method
+            .inner
+            .inputs
+            .iter()
+            .map(|(name, shape)| format!("{} {}", emit_shape(shape), name))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ));
+    output.push_str(&format!(
+        "    {}await _request(\"{}\", <String, dynamic> {{ {} }});\n{}",
+        match method.inner.output {
+            None => "",
+            Some(_) => "var response = ",
+        },
+        name,
+        method
+            .inner
+            .inputs
+            .iter()
+            .map(|(name, shape)| format!("\"{}\": {}", name, serialize_shape(name, shape)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        match method.inner.output {
+            None => "".to_owned(),
+            Some(ref shape) => format!("    return {};\n", deserialize_shape("response", shape)),
+        },
+    ));
+    output.push_str("  }\n");
+
+    output
+}
+
+fn emit_provider(name: &str, service: &Service) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("abstract class {}Provider ", name));
+    output.push_str("{\n");
+    service.methods.iter().for_each(|(name, def)| {
+        output.push_str(&format!(
+            "  Future<{}> {}({});\n",
+            match def.inner.output {
+                None => "void".to_owned(),
+                Some(ref shape) => emit_shape(shape),
+            },
+            name,
+            def.inner
+                .inputs
+                .iter()
+                .map(|(name, shape)| format!("{} {}", emit_shape(shape), name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    });
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_dispatcher(name: &str, service: &Service) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("class {}Dispatcher ", name));
+    output.push_str("{\n\n");
+    output.push_str(&format!("  final {}Provider provider;\n", name));
+    output.push('\n');
+    output.push_str(&format!("  {}Dispatcher(this.provider);\n", name));
+    output.push('\n');
+    output.push_str("  Future<Map<String, dynamic>> handle(Map<String, dynamic> request) =>\n");
+    output.push_str(&format!("      respond(\"{}\", request, dispatch);\n", name));
+    output.push('\n');
+    output.push_str(
+        "  Future<dynamic> dispatch(String method, Map<String, dynamic> params) async {\n",
+    );
+    output.push_str("    switch (method) {\n");
+    service.methods.iter().for_each(|(method_name, def)| {
+        let inputs = &def.inner.inputs;
+
+        output.push_str(&format!("      case \"{}\": {{\n", method_name));
+        if !inputs.is_empty() {
+            output.push_str(&format!(
+                "          var inputs = decodeParams(() => [{}]);\n",
+                inputs
+                    .iter()
+                    .map(|(name, shape)| deserialize_shape(&format!("params['{}']", name), shape))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        let call = format!(
+            "provider.{}({})",
+            method_name,
+            (0..inputs.iter().count())
+                .map(|index| format!("inputs[{}]", index))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        match def.inner.output {
+            None => {
+                output.push_str(&format!("          await {};\n", call));
+                output.push_str("          return null;\n");
+            }
+            Some(ref shape) => {
+                output.push_str(&format!("          var response = await {};\n", call));
+                output.push_str(&format!(
+                    "          return {};\n",
+                    serialize_shape("response", shape)
+                ));
+            }
+        }
+        output.push_str("      }\n");
+    });
+    output.push_str("      default:\n");
+    output.push_str(&format!(
+        "        throw RpcException('{}', 'unknown method $method of {}');\n",
+        rpc::UNKNOWN_METHOD,
+        name
+    ));
+    output.push_str("    }\n");
+    output.push_str("  }\n");
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_alias(name: &str, alias: &Alias) -> String {
+    format!(
+        "typedef {}{} = {};\n",
+        name,
+        emit_type_parameters(&alias.type_parameters),
+        emit_shape(&alias.shape.inner)
+    )
+}
+
+fn emit_model(name: &str, def: &Model) -> String {
+    match def {
+        Model::Struct(inner) => emit_struct(name, inner),
+        Model::Enum(inner) => emit_enum(name, inner),
+        Model::Union(inner) => emit_union(name, inner),
+        Model::Alias(inner) => emit_alias(name, inner),
+        // no need to emit external models, they are already imported
+        Model::External(_) => "".to_owned(),
+    }
+}
+
+fn emit_enum(name: &str, def: &Enum) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("enum {} ", name));
+    output.push_str("{\n");
+
+    // Emit variants
+    output.push_str(
+        &def.fields
+            .iter()
+            .map(|v| format!("  {}", v))
+            .collect::<Vec<_>>()
+            .join(",\n"),
+    );
+    output.push_str(";\n");
+    output.push('\n');
+
+    // Emit toJson method
+    output.push_str(&format!("  String toJson() => ${}ToJson(this);\n", name));
+
+    // Emit fromJson method
+    output.push_str(&format!(
+        "  factory {}.fromJson(String json) => ${}FromJson(json);\n",
+        name, name
+    ));
+
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_union(name: &str, def: &Union) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("sealed class {} ", name));
+    output.push_str("{\n");
+    output.push_str(&format!("  {}();\n", name));
+    output.push('\n');
+
+    // Emit toJson method
+    output.push_str(&format!(
+        "  Map<String, dynamic> toJson() => ${}ToJson(this);\n",
+        name
+    ));
+
+    // Emit fromJson method
+    output.push_str(&format!(
+        "  factory {}.fromJson(Map<String, dynamic> json) => ${}FromJson(json);\n",
+        name, name
+    ));
+    output.push_str("}\n");
+
+    // Emit one subclass per variant
+    def.variants.iter().for_each(|(variant_name, variant)| {
+        let class_name = format!("{}{}", name, variant_name);
+
+        output.push('\n');
+        output.push_str(&format!("class {} extends {} ", class_name, name));
+        output.push_str("{\n");
+        match variant {
+            Variant::Unit => {
+                output.push_str(&format!("  {}();\n", class_name));
+            }
+            Variant::Tuple(shape) => {
+                output.push_str(&format!("  {} value;\n", emit_shape(shape)));
+                output.push('\n');
+                output.push_str(&format!("  {}(this.value);\n", class_name));
+            }
+            Variant::Struct(fields) => {
+                fields.iter().for_each(|(name, shape)| {
+                    output.push_str(&format!("  {} {};\n", emit_shape(&shape.inner), name));
+                });
+                output.push('\n');
+                output.push_str(&emit_constructor(&class_name, fields));
+            }
+        }
+        output.push_str("}\n");
+    });
+
+    output
+}
+
+fn emit_struct(name: &str, def: &Struct) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "class {}{} ",
+        name,
+        emit_type_parameters(&def.type_parameters)
+    ));
+    output.push_str("{\n");
+
+    // Emit fields
+    def.fields.iter().for_each(|(name, shape)| {
+        output.push_str(&format!("  {} {};\n", emit_shape(&shape.inner), name));
+    });
+    output.push('\n');
+
+    // Emit constructor
+    output.push_str(&emit_constructor(name, &def.fields));
+    output.push('\n');
+
+    // Emit toJson method, taking an encoder per type parameter
+    output.push_str(&format!(
+        "  Map<String, dynamic> toJson({}) => ${}ToJson(this{});\n",
+        serializer_parameters(&def.type_parameters).join(", "),
+        name,
+        forward_serializers(&def.type_parameters),
+    ));
+
+    // Emit fromJson method, taking a decoder per type parameter
+    output.push_str(&format!(
+        "  factory {}.fromJson({}) => ${}FromJson(json{});\n",
+        name,
+        ["Map<String, dynamic> json".to_owned()]
+            .into_iter()
+            .chain(deserializer_parameters(&def.type_parameters))
+            .collect::<Vec<_>>()
+            .join(", "),
+        name,
+        forward_deserializers(&def.type_parameters),
+    ));
+
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_type_parameters(type_parameters: &[&str]) -> String {
+    if type_parameters.is_empty() {
+        return "".to_owned();
+    }
+
+    format!("<{}>", type_parameters.join(", "))
+}
+
+// Encoders and decoders of type parameters are named like the helpers of regular
+// models, so references to a type parameter resolve to the passed function.
+fn serializer_parameters(type_parameters: &[&str]) -> Vec<String> {
+    type_parameters
+        .iter()
+        .map(|name| format!("dynamic Function({}) ${}ToJson", name, name))
+        .collect()
+}
+
+fn deserializer_parameters(type_parameters: &[&str]) -> Vec<String> {
+    type_parameters
+        .iter()
+        .map(|name| format!("{} Function(dynamic) ${}FromJson", name, name))
+        .collect()
+}
+
+fn forward_serializers(type_parameters: &[&str]) -> String {
+    type_parameters
+        .iter()
+        .map(|name| format!(", ${}ToJson", name))
+        .collect()
+}
+
+fn forward_deserializers(type_parameters: &[&str]) -> String {
+    type_parameters
+        .iter()
+        .map(|name| format!(", ${}FromJson", name))
+        .collect()
+}
+
+fn emit_constructor(name: &str, fields: &OrderedHashMap<&str, Annotated<Shape>>) -> String {
+    let mut output = String::new();
+
+    output.push_str(format!("  {}({{\n", name).as_str());
+    fields.iter().for_each(|(name, shape)| {
+        let optional = matches!(shape.inner, Shape::Nullable(_));
+
+        output.push_str(&format!(
+            "    {}this.{},\n",
+            if optional { "" } else { "required " },
+            name,
+        ));
+    });
+    output.push_str("  });\n");
+
+    output
+}
+
+fn emit_shape(shape: &Shape) -> String {
+    match shape {
+        Shape::Bool => "bool".to_owned(),
+        Shape::Int8 => "int".to_owned(),
+        Shape::Int16 => "int".to_owned(),
+        Shape::Int32 => "int".to_owned(),
+        Shape::Int64 => "int".to_owned(),
+        Shape::Float32 => "double".to_owned(),
+        Shape::Float64 => "double".to_owned(),
+        Shape::String => "String".to_owned(),
+        Shape::Nullable(inner) => format!("{}?", emit_shape(inner)),
+        Shape::List(inner) => format!("List<{}>", emit_shape(inner)),
+        Shape::Map(key, value) => format!("Map<{}, {}>", emit_shape(key), emit_shape(value)),
+        Shape::Reference(name, arguments) if arguments.is_empty() => (*name).to_owned(),
+        Shape::Reference(name, arguments) => format!(
+            "{}<{}>",
+            name,
+            arguments
+                .iter()
+                .map(|argument| emit_shape(argument))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn serialize_model(name: &str, model: &Model) -> String {
+    match model {
+        Model::Struct(inner) => serialize_struct(name, inner),
+        Model::Enum(inner) => serialize_enum(name, inner),
+        Model::Union(inner) => serialize_union(name, inner),
+        Model::Alias(inner) => serialize_alias(name, inner),
+        Model::External(_) => format!(
+            "/* Map<String, dynamic> ${}ToJson({} instance) => ? */",
+            name, name
+        ),
+    }
+}
+
+fn deserialize_model(name: &str, model: &Model) -> String {
+    match model {
+        Model::Struct(inner) => deserialize_struct(name, inner),
+        Model::Enum(inner) => deserialize_enum(name, inner),
+        Model::Union(inner) => deserialize_union(name, inner),
+        Model::Alias(inner) => deserialize_alias(name, inner),
+        Model::External(_) => format!(
+            "/* {} ${}FromJson(Map<String, dynamic> json) => ? */",
+            name, name
+        ),
+    }
+}
+
+fn serialize_struct(name: &str, def: &Struct) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "Map<String, dynamic> ${}ToJson{}({}{} instance{}) => <String, dynamic>{{",
+        name,
+        emit_type_parameters(&def.type_parameters),
+        name,
+        emit_type_parameters(&def.type_parameters),
+        serializer_parameters(&def.type_parameters)
+            .iter()
+            .map(|parameter| format!(", {}", parameter))
+            .collect::<String>(),
+    ));
+    output.push_str(
+        &def.fields
+            .iter()
+            .map(|(name, shape)| {
+                format!(
+                    "'{}':{}",
+                    name,
+                    serialize_shape(&format!("instance.{}", name), &shape.inner)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    output.push_str("};");
+
+    output
+}
+
+fn serialize_enum(name: &str, def: &Enum) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("dynamic ${}ToJson({} instance)=>", name, name));
+    output.push_str("switch(instance){");
+    output.push_str(
+        &def.fields
+            .iter()
+            .map(|v| format!("{}.{}=>'{}'", name, v, v))
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    output.push_str("};");
+
+    output
+}
+
+fn deserialize_enum(name: &str, def: &Enum) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("{} ${}FromJson(String value)=>", name, name));
+    output.push_str("switch(value){");
+    output.push_str(
+        &def.fields
+            .iter()
+            .map(|v| format!("'{}'=>{}.{},", v, name, v))
+            .collect::<Vec<_>>()
+            .join(""),
+    );
+    output.push_str(&format!("_=>throw'Unknown {} value: $value'", name));
+    output.push_str("};");
+
+    output
+}
+
+fn serialize_union(name: &str, def: &Union) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "Map<String, dynamic> ${}ToJson({} instance)=>",
+        name, name
+    ));
+    output.push_str("switch(instance){");
+    output.push_str(
+        &def.variants
+            .iter()
+            .map(|(variant_name, variant)| {
+                let tag = format!("'{}':'{}'", def.tag(), variant_name);
+                let content = match variant {
+                    Variant::Unit => "".to_owned(),
+                    Variant::Tuple(shape) => format!(
+                        ",'{}':{}",
+                        def.content(),
+                        serialize_shape("i.value", shape)
+                    ),
+                    Variant::Struct(fields) => format!(
+                        ",'{}':<String, dynamic>{{{}}}",
+                        def.content(),
+                        fields
+                            .iter()
+                            .map(|(name, shape)| {
+                                format!(
+                                    "'{}':{}",
+                                    name,
+                                    serialize_shape(&format!("i.{}", name), &shape.inner)
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    ),
+                };
+                format!(
+                    "{}{} i=><String, dynamic>{{{}{}}}",
+                    name, variant_name, tag, content
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    output.push_str("};");
+
+    output
+}
+
+fn deserialize_union(name: &str, def: &Union) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "{} ${}FromJson(Map<String,dynamic>json)=>",
+        name, name
+    ));
+    output.push_str(&format!("switch(json['{}']){{", def.tag()));
+    output.push_str(
+        &def.variants
+            .iter()
+            .map(|(variant_name, variant)| {
+                let content = format!("json['{}']", def.content());
+                let constructor = match variant {
+                    Variant::Unit => "()".to_owned(),
+                    Variant::Tuple(shape) => format!("({})", deserialize_shape(&content, shape)),
+                    Variant::Struct(fields) => format!(
+                        "({})",
+                        fields
+                            .iter()
+                            .map(|(name, shape)| {
+                                format!(
+                                    "{}:{}",
+                                    name,
+                                    deserialize_shape(
+                                        &format!("{}['{}']", content, name),
+                                        &shape.inner
+                                    )
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    ),
+                };
+                format!("'{}'=>{}{}{},", variant_name, name, variant_name, constructor)
+            })
+            .collect::<Vec<_>>()
+            .join(""),
+    );
+    output.push_str(&format!(
+        "_=>throw'Unknown {} variant: ${{json['{}']}}'",
+        name,
+        def.tag()
+    ));
+    output.push_str("};");
+
+    output
+}
+
+fn serialize_alias(name: &str, alias: &Alias) -> String {
+    format!(
+        "dynamic ${}ToJson{}({}{} instance{}) => {};",
+        name,
+        emit_type_parameters(&alias.type_parameters),
+        name,
+        emit_type_parameters(&alias.type_parameters),
+        serializer_parameters(&alias.type_parameters)
+            .iter()
+            .map(|parameter| format!(", {}", parameter))
+            .collect::<String>(),
+        serialize_shape("instance", &alias.shape.inner)
+    )
+}
+
+fn deserialize_alias(name: &str, alias: &Alias) -> String {
+    format!(
+        "{}{} ${}FromJson{}(dynamic json{}) => {};",
+        name,
+        emit_type_parameters(&alias.type_parameters),
+        name,
+        emit_type_parameters(&alias.type_parameters),
+        deserializer_parameters(&alias.type_parameters)
+            .iter()
+            .map(|parameter| format!(", {}", parameter))
+            .collect::<String>(),
+        deserialize_shape("json", &alias.shape.inner)
+    )
+}
+
+fn deserialize_struct(name: &str, def: &Struct) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "{}{} ${}FromJson{}(Map<String,dynamic>json{})=>{}{}(",
+        name,
+        emit_type_parameters(&def.type_parameters),
+        name,
+        emit_type_parameters(&def.type_parameters),
+        deserializer_parameters(&def.type_parameters)
+            .iter()
+            .map(|parameter| format!(",{}", parameter))
+            .collect::<String>(),
+        name,
+        emit_type_parameters(&def.type_parameters),
+    ));
+    output.push_str(
+        &def.fields
+            .iter()
+            .map(|(name, shape)| {
+                format!(
+                    "{}:{}",
+                    name,
+                    deserialize_shape(&format!("json['{}']", name), &shape.inner)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    output.push_str(");");
+
+    output
+}
+
+fn serialize_shape(name: &str, shape: &Shape) -> String {
+    match shape {
+        Shape::Nullable(inner) => format!(
+            "{} == null ? null : {}",
+            name,
+            serialize_shape(&format!("{} as {}", name, emit_shape(inner)), inner),
+        ),
+        Shape::Reference(type_name, arguments) => {
+            format!(
+                "${}ToJson({}{})",
+                type_name,
+                name,
+                arguments
+                    .iter()
+                    .map(|argument| format!(",(e)=>{}", serialize_shape("e", argument)))
+                    .collect::<String>()
+            )
+        }
+        Shape::List(inner) if has_references(inner) => format!(
+            "{}.map((e) => {}).toList()",
+            receiver(name),
+            serialize_shape("e", inner)
+        ),
+        Shape::Map(key, value) if has_references(key) || has_references(value) => format!(
+            "{}.map((k,v) => MapEntry({},{}))",
+            receiver(name),
+            serialize_shape("k", key),
+            serialize_shape("v", value),
+        ),
+        _ => name.to_string(),
+    }
+}
+
+/// Wraps casts in parentheses, so methods can be called on their result
+fn receiver(name: &str) -> String {
+    match name.contains(" as ") {
+        true => format!("({})", name),
+        false => name.to_string(),
+    }
+}
+
+/// Whether values of a shape have to be converted to become JSON
+fn has_references(shape: &Shape) -> bool {
+    match shape {
+        Shape::Nullable(inner) => has_references(inner),
+        Shape::List(inner) => has_references(inner),
+        Shape::Map(key, value) => has_references(key) || has_references(value),
+        Shape::Reference(..) => true,
+        _ => false,
+    }
+}
+
+fn deserialize_shape(field_name: &str, shape: &Shape) -> String {
+    match shape {
+        Shape::Bool => format!("{} as bool", field_name),
+        Shape::Int8 => format!("{} as int", field_name),
+        Shape::Int16 => format!("{} as int", field_name),
+        Shape::Int32 => format!("{} as int", field_name),
+        Shape::Int64 => format!("{} as int", field_name),
+        Shape::Float32 => format!("{} as double", field_name),
+        Shape::Float64 => format!("{} as double", field_name),
+        Shape::String => format!("{} as String", field_name),
+        Shape::Nullable(inner) => format!(
+            "{} == null ? null : {}",
+            field_name,
+            deserialize_shape(field_name, inner),
+        ),
+        Shape::List(inner) => format!(
+            "({} as List<dynamic>).map((e) => {}).toList()",
+            field_name,
+            deserialize_shape("e", inner)
+        ),
+        Shape::Map(key, value) => format!(
+            "({} as Map<String,dynamic>).map((k,v) => MapEntry({},{}))",
+            field_name,
+            deserialize_shape("k", key),
+            deserialize_shape("v", value),
+        ),
+        Shape::Reference(name, arguments) => format!(
+            "${}FromJson({}{})",
+            name,
+            field_name,
+            arguments
+                .iter()
+                .map(|argument| format!(",(e)=>{}", deserialize_shape("e", argument)))
+                .collect::<String>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let module = module(SCHEMA);
+        assert_golden("todo.dart", &emit_schema("todo", &module.schema, &rpc::path(0)));
+    }
+
+    #[test]
+    fn emits_golden_runtime() {
+        assert_golden("rpc.dart", &emit_runtime());
+    }
+}
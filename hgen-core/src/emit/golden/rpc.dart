@@ -0,0 +1,48 @@
+// AUTOGENERATED FILE - DO NOT EDIT
+
+abstract class RequestHandler {
+  Future<Map<String, dynamic>> request(Map<String, dynamic> request);
+}
+
+class RpcException implements Exception {
+  final String code;
+  final String message;
+
+  RpcException(this.code, this.message);
+
+  @override
+  String toString() => 'RpcException($code): $message';
+}
+
+List<dynamic> decodeParams(List<dynamic> Function() decode) {
+  try {
+    return decode();
+  } catch (error) {
+    throw RpcException('invalid_params', error.toString());
+  }
+}
+
+Future<Map<String, dynamic>> respond(
+  String service,
+  Map<String, dynamic> request,
+  Future<dynamic> Function(String, Map<String, dynamic>) dispatch,
+) async {
+  try {
+    if (request['version'] != 1) {
+      throw RpcException('unsupported_version', 'unsupported version ${request['version']}');
+    }
+    if (request['service'] != service) {
+      throw RpcException('unknown_service', 'unknown service ${request['service']}');
+    }
+    var params = request['params'];
+    if (params is! Map<String, dynamic>) {
+      throw RpcException('invalid_params', 'params have to be an object');
+    }
+    var result = await dispatch(request['method'] as String, params);
+    return <String, dynamic>{'version': 1, 'id': request['id'], 'result': result};
+  } catch (error) {
+    var code = error is RpcException ? error.code : 'internal';
+    var message = error is RpcException ? error.message : error.toString();
+    return <String, dynamic>{'version': 1, 'id': request['id'], 'error': <String, dynamic>{'code': code, 'message': message}};
+  }
+}
@@ -0,0 +1,13 @@
+# AUTOGENERATED FILE - DO NOT EDIT
+
+from typing import Any, Dict
+
+
+RpcRequest = Dict[str, Any]
+RpcResponse = Dict[str, Any]
+
+
+class RpcError(Exception):
+    def __init__(self, code: str, message: str) -> None:
+        super().__init__(message)
+        self.code = code
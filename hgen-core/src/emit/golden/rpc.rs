@@ -0,0 +1,118 @@
+use serde::{Serialize, Deserialize};
+
+/// Version of the envelope of requests and responses
+pub const RPC_VERSION: u32 = 1;
+
+/// Request to call a method of a service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRequest {
+    pub version: u32,
+    /// Identifies the response to this request, either a number or a string
+    pub id: serde_json::Value,
+    pub service: String,
+    pub method: String,
+    /// Inputs of the method, keyed by their names in the schema
+    pub params: serde_json::Value,
+}
+
+/// Response to a request, carrying the same id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub version: u32,
+    pub id: serde_json::Value,
+    #[serde(flatten)]
+    pub outcome: RpcOutcome,
+}
+
+impl RpcResponse {
+    pub fn new(id: serde_json::Value, outcome: Result<serde_json::Value, RpcError>) -> Self {
+        Self {
+            version: RPC_VERSION,
+            id,
+            outcome: match outcome {
+                Ok(result) => RpcOutcome::Result(result),
+                Err(error) => RpcOutcome::Error(error),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcOutcome {
+    /// Serialized output of the method, `null` for methods without one
+    Result(serde_json::Value),
+    Error(RpcError),
+}
+
+/// Error of a request, with a `code` of `unsupported_version`, `unknown_service`,
+/// `unknown_method`, `invalid_params` or `internal`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: String,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_owned(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Sends requests of clients to the providers of services, e.g. over HTTP
+pub trait Transport {
+    type Error: From<serde_json::Error> + From<RpcError>;
+
+    fn send(
+        &self,
+        request: RpcRequest,
+    ) -> impl std::future::Future<Output = Result<RpcResponse, Self::Error>> + Send;
+}
+
+/// Reasons a provider could not handle a request
+#[derive(Debug)]
+pub enum DispatchError<E> {
+    /// The service has no method of the given name
+    UnknownMethod(String),
+    /// The params do not match the inputs of the method
+    InvalidParams(serde_json::Error),
+    /// The output of the method could not be serialized
+    InvalidOutput(serde_json::Error),
+    /// The provider returned an error
+    Provider(E),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for DispatchError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownMethod(method) => write!(f, "unknown method `{}`", method),
+            Self::InvalidParams(error) => write!(f, "invalid params: {}", error),
+            Self::InvalidOutput(error) => write!(f, "invalid output: {}", error),
+            Self::Provider(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for DispatchError<E> {}
+
+impl<E: std::fmt::Display> From<DispatchError<E>> for RpcError {
+    fn from(error: DispatchError<E>) -> Self {
+        let code = match error {
+            DispatchError::UnknownMethod(_) => "unknown_method",
+            DispatchError::InvalidParams(_) => "invalid_params",
+            DispatchError::InvalidOutput(_) | DispatchError::Provider(_) => "internal",
+        };
+        RpcError::new(code, error.to_string())
+    }
+}
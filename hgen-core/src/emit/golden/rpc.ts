@@ -0,0 +1,52 @@
+// AUTOGENERATED FILE - DO NOT EDIT
+
+export interface RpcRequest {
+  version: 1;
+  id: number | string;
+  service: string;
+  method: string;
+  params: { [name: string]: any };
+}
+
+export type RpcResponse =
+  | { version: 1; id: number | string; result: any }
+  | { version: 1; id: number | string; error: { code: string; message: string } };
+
+export class RpcError extends Error {
+  constructor(public code: string, message: string) {
+    super(message);
+  }
+}
+
+export function requireParams(params: { [name: string]: any }, names: string[]) {
+  for (const name of names) {
+    if (params[name] === undefined || params[name] === null) {
+      throw new RpcError('invalid_params', `missing param ${name}`);
+    }
+  }
+}
+
+export async function respond(
+  service: string,
+  request: RpcRequest,
+  dispatch: (method: string, params: any) => Promise<any>
+): Promise<RpcResponse> {
+  try {
+    if (request.version !== 1) {
+      throw new RpcError('unsupported_version', `unsupported version ${request.version}`);
+    }
+    if (request.service !== service) {
+      throw new RpcError('unknown_service', `unknown service ${request.service}`);
+    }
+    const params: unknown = request.params;
+    if (typeof params !== 'object' || params === null || Array.isArray(params)) {
+      throw new RpcError('invalid_params', 'params have to be an object');
+    }
+    const result = await dispatch(request.method, params);
+    return { version: 1, id: request.id, result: result ?? null };
+  } catch (error) {
+    const code = error instanceof RpcError ? error.code : 'internal';
+    const message = error instanceof Error ? error.message : String(error);
+    return { version: 1, id: request.id, error: { code, message } };
+  }
+}
@@ -0,0 +1,157 @@
+// AUTOGENERATED FILE - DO NOT EDIT
+
+import 'todo.external.dart';
+export 'todo.external.dart';
+import './rpc.dart';
+export './rpc.dart' show RequestHandler, RpcException;
+
+typedef Counts = Map<String, int>;
+
+class Page<T> {
+  List<T> items;
+  String? next;
+
+  Page({
+    required this.items,
+    this.next,
+  });
+
+  Map<String, dynamic> toJson(dynamic Function(T) $TToJson) => $PageToJson(this, $TToJson);
+  factory Page.fromJson(Map<String, dynamic> json, T Function(dynamic) $TFromJson) => $PageFromJson(json, $TFromJson);
+}
+
+class Todo {
+  int id;
+  String title;
+  List<String> tags;
+  Counts counts;
+  String? note;
+  Status status;
+
+  Todo({
+    required this.id,
+    required this.title,
+    required this.tags,
+    required this.counts,
+    this.note,
+    required this.status,
+  });
+
+  Map<String, dynamic> toJson() => $TodoToJson(this);
+  factory Todo.fromJson(Map<String, dynamic> json) => $TodoFromJson(json);
+}
+
+enum Status {
+  Open,
+  Done;
+
+  String toJson() => $StatusToJson(this);
+  factory Status.fromJson(String json) => $StatusFromJson(json);
+}
+
+sealed class Event {
+  Event();
+
+  Map<String, dynamic> toJson() => $EventToJson(this);
+  factory Event.fromJson(Map<String, dynamic> json) => $EventFromJson(json);
+}
+
+class EventCreated extends Event {
+  Todo value;
+
+  EventCreated(this.value);
+}
+
+class EventClosed extends Event {
+  int id;
+
+  EventClosed({
+    required this.id,
+  });
+}
+
+class EventCleared extends Event {
+  EventCleared();
+}
+
+class TodoServiceConsumer {
+
+  final RequestHandler handler;
+  final String name = "TodoService";
+  int _id = 0;
+
+  TodoServiceConsumer(this.handler);
+
+  Future<dynamic> _request(String method, Map<String, dynamic> params) async {
+    var response = await handler.request(<String, dynamic>{'version': 1, 'id': ++_id, 'service': name, 'method': method, 'params': params});
+    if (response['error'] != null) {
+      throw RpcException(response['error']['code'], response['error']['message']);
+    }
+    return response['result'];
+  }
+
+  Future<Todo?> get(int id) async {
+    var response = await _request("get", <String, dynamic> { "id": id });
+    return response == null ? null : $TodoFromJson(response);
+  }
+
+  Future<Page<Todo>> getAll(String? cursor) async {
+    var response = await _request("getAll", <String, dynamic> { "cursor": cursor == null ? null : cursor as String });
+    return $PageFromJson(response,(e)=>$TodoFromJson(e));
+  }
+
+  Future<void> close(int id) async {
+    await _request("close", <String, dynamic> { "id": id });
+  }
+}
+
+abstract class TodoServiceProvider {
+  Future<Todo?> get(int id);
+  Future<Page<Todo>> getAll(String? cursor);
+  Future<void> close(int id);
+}
+
+class TodoServiceDispatcher {
+
+  final TodoServiceProvider provider;
+
+  TodoServiceDispatcher(this.provider);
+
+  Future<Map<String, dynamic>> handle(Map<String, dynamic> request) =>
+      respond("TodoService", request, dispatch);
+
+  Future<dynamic> dispatch(String method, Map<String, dynamic> params) async {
+    switch (method) {
+      case "get": {
+          var inputs = decodeParams(() => [params['id'] as int]);
+          var response = await provider.get(inputs[0]);
+          return response == null ? null : $TodoToJson(response as Todo);
+      }
+      case "getAll": {
+          var inputs = decodeParams(() => [params['cursor'] == null ? null : params['cursor'] as String]);
+          var response = await provider.getAll(inputs[0]);
+          return $PageToJson(response,(e)=>$TodoToJson(e));
+      }
+      case "close": {
+          var inputs = decodeParams(() => [params['id'] as int]);
+          await provider.close(inputs[0]);
+          return null;
+      }
+      default:
+        throw RpcException('unknown_method', 'unknown method $method of TodoService');
+    }
+  }
+}
+
+dynamic $CountsToJson(Counts instance) => instance;
+Map<String, dynamic> $PageToJson<T>(Page<T> instance, dynamic Function(T) $TToJson) => <String, dynamic>{'items':instance.items.map((e) => $TToJson(e)).toList(),'next':instance.next == null ? null : instance.next as String};
+Map<String, dynamic> $TodoToJson(Todo instance) => <String, dynamic>{'id':instance.id,'title':instance.title,'tags':instance.tags,'counts':$CountsToJson(instance.counts),'note':instance.note == null ? null : instance.note as String,'status':$StatusToJson(instance.status)};
+dynamic $StatusToJson(Status instance)=>switch(instance){Status.Open=>'Open',Status.Done=>'Done'};
+Map<String, dynamic> $EventToJson(Event instance)=>switch(instance){EventCreated i=><String, dynamic>{'type':'Created','value':$TodoToJson(i.value)},EventClosed i=><String, dynamic>{'type':'Closed','value':<String, dynamic>{'id':i.id}},EventCleared i=><String, dynamic>{'type':'Cleared'}};
+
+Counts $CountsFromJson(dynamic json) => (json as Map<String,dynamic>).map((k,v) => MapEntry(k as String,v as int));
+Page<T> $PageFromJson<T>(Map<String,dynamic>json,T Function(dynamic) $TFromJson)=>Page<T>(items:(json['items'] as List<dynamic>).map((e) => $TFromJson(e)).toList(),next:json['next'] == null ? null : json['next'] as String);
+Todo $TodoFromJson(Map<String,dynamic>json)=>Todo(id:json['id'] as int,title:json['title'] as String,tags:(json['tags'] as List<dynamic>).map((e) => e as String).toList(),counts:$CountsFromJson(json['counts']),note:json['note'] == null ? null : json['note'] as String,status:$StatusFromJson(json['status']));
+Status $StatusFromJson(String value)=>switch(value){'Open'=>Status.Open,'Done'=>Status.Done,_=>throw'Unknown Status value: $value'};
+Event $EventFromJson(Map<String,dynamic>json)=>switch(json['type']){'Created'=>EventCreated($TodoFromJson(json['value'])),'Closed'=>EventClosed(id:json['value']['id'] as int),'Cleared'=>EventCleared(),_=>throw'Unknown Event variant: ${json['type']}'};
+
@@ -0,0 +1,177 @@
+# AUTOGENERATED FILE - DO NOT EDIT
+
+from __future__ import annotations
+
+import dataclasses
+import enum
+import json
+import typing
+from typing import Any, Callable, Dict, Generic, List, Literal, Optional, TypeVar, Union
+
+from .rpc import RpcError, RpcRequest, RpcResponse
+
+
+T = TypeVar("T")
+
+
+@dataclasses.dataclass
+class Page(Generic[T]):
+    items: List[T]
+    next: Optional[str]
+
+
+@dataclasses.dataclass
+class Todo:
+    id: int
+    title: str
+    tags: List[str]
+    counts: Counts
+    note: Optional[str]
+    status: Status
+
+
+class Status(enum.Enum):
+    Open = "Open"
+    Done = "Done"
+
+
+@dataclasses.dataclass
+class EventCreated:
+    value: Todo
+    type: Literal["Created"] = "Created"
+
+
+@dataclasses.dataclass
+class EventClosedPayload:
+    id: int
+
+
+@dataclasses.dataclass
+class EventClosed:
+    value: EventClosedPayload
+    type: Literal["Closed"] = "Closed"
+
+
+@dataclasses.dataclass
+class EventCleared:
+    type: Literal["Cleared"] = "Cleared"
+
+
+Event = Union[EventCreated, EventClosed, EventCleared]
+
+
+Counts = Dict[str, int]
+
+
+def encode(value: Any) -> Any:
+    """Converts a value into its JSON representation."""
+    if isinstance(value, enum.Enum):
+        return value.value
+    if dataclasses.is_dataclass(value):
+        return {
+            field.metadata.get("name", field.name): encode(getattr(value, field.name))
+            for field in dataclasses.fields(value)
+        }
+    if isinstance(value, (list, tuple)):
+        return [encode(item) for item in value]
+    if isinstance(value, dict):
+        return {_encode_key(key): encode(item) for key, item in value.items()}
+    return value
+
+
+def decode(data: Any, type_: Any) -> Any:
+    """Converts the JSON representation of a value of the given type into the value."""
+    origin = typing.get_origin(type_)
+    arguments = typing.get_args(type_)
+    if origin is Union:
+        if data is None and type(None) in arguments:
+            return None
+        variants = [argument for argument in arguments if argument is not type(None)]
+        if len(variants) == 1:
+            return decode(data, variants[0])
+        for variant in variants:
+            tag, name = _variant_tag(variant)
+            if data.get(tag) == name:
+                return decode(data, variant)
+        raise ValueError(f"unknown variant {data!r}")
+    if origin is list:
+        return [decode(item, arguments[0]) for item in data]
+    if origin is dict:
+        return {_decode_key(key, arguments[0]): decode(item, arguments[1]) for key, item in data.items()}
+    if origin is Literal or isinstance(type_, TypeVar):
+        return data
+    model = origin or type_
+    if isinstance(model, type) and issubclass(model, enum.Enum):
+        return model(data)
+    if dataclasses.is_dataclass(model):
+        hints = typing.get_type_hints(model)
+        bindings = dict(zip(getattr(model, "__parameters__", ()), arguments))
+        return model(**{
+            field.name: decode(data.get(field.metadata.get("name", field.name)), _bind(hints[field.name], bindings))
+            for field in dataclasses.fields(model)
+        })
+    if model is float and data is not None:
+        return float(data)
+    return data
+
+
+def to_json(value: Any) -> str:
+    return json.dumps(encode(value))
+
+
+def from_json(text: Union[str, bytes], type_: Any) -> Any:
+    return decode(json.loads(text), type_)
+
+
+def _encode_key(key: Any) -> str:
+    key = encode(key)
+    if isinstance(key, bool):
+        return "true" if key else "false"
+    return str(key)
+
+
+def _decode_key(key: str, type_: Any) -> Any:
+    if type_ is bool:
+        return key == "true"
+    if type_ is int:
+        return int(key)
+    return decode(key, type_)
+
+
+def _variant_tag(variant: Any) -> Any:
+    hints = typing.get_type_hints(variant)
+    for field in dataclasses.fields(variant):
+        if typing.get_origin(hints[field.name]) is Literal:
+            return field.metadata.get("name", field.name), field.default
+    raise TypeError(f"{variant!r} is not a variant")
+
+
+def _bind(type_: Any, bindings: Dict[Any, Any]) -> Any:
+    if isinstance(type_, TypeVar):
+        return bindings.get(type_, type_)
+    parameters = getattr(type_, "__parameters__", ())
+    if parameters and bindings:
+        return type_[tuple(bindings.get(parameter, parameter) for parameter in parameters)]
+    return type_
+
+
+class TodoServiceConsumer:
+    def __init__(self, send: Callable[[RpcRequest], RpcResponse]) -> None:
+        self.send = send
+        self._id = 0
+
+    def _request(self, method: str, params: Dict[str, Any]) -> Any:
+        self._id += 1
+        response = self.send({"version": 1, "id": self._id, "service": "TodoService", "method": method, "params": params})
+        if "error" in response:
+            raise RpcError(response["error"]["code"], response["error"]["message"])
+        return response.get("result")
+
+    def get(self, id: int) -> Optional[Todo]:
+        return decode(self._request("get", {"id": encode(id)}), Optional[Todo])
+
+    def get_all(self, cursor: Optional[str]) -> Page[Todo]:
+        return decode(self._request("getAll", {"cursor": encode(cursor)}), Page[Todo])
+
+    def close(self, id: int) -> None:
+        self._request("close", {"id": encode(id)})
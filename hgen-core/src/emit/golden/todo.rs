@@ -0,0 +1,163 @@
+use serde::{Serialize, Deserialize};
+use super::rpc::*;
+pub type Counts = std::collections::HashMap<String, i32>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Todo {
+    pub id: i64,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub counts: Counts,
+    pub note: Option<String>,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Status {
+    Open,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum Event {
+    Created(Todo),
+    Closed {
+        id: i64,
+    },
+    Cleared,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoServiceGetInput {
+    pub id: i64,
+}
+
+pub type TodoServiceGetOutput = Option<Todo>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoServiceGetAllInput {
+    pub cursor: Option<String>,
+}
+
+pub type TodoServiceGetAllOutput = Page<Todo>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoServiceCloseInput {
+    pub id: i64,
+}
+
+pub type TodoServiceCloseOutput = ();
+
+pub trait TodoServiceProvider {
+    type Error;
+
+    fn get(&self, id: i64) -> impl std::future::Future<Output = Result<Option<Todo>, Self::Error>> + Send;
+
+    fn get_all(&self, cursor: Option<String>) -> impl std::future::Future<Output = Result<Page<Todo>, Self::Error>> + Send;
+
+    fn close(&self, id: i64) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
+}
+
+/// Decodes the params of a request to `TodoService`, calls the matching method of
+/// the provider and encodes its output
+pub async fn dispatch_todo_service<P: TodoServiceProvider>(
+    provider: &P,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, DispatchError<P::Error>> {
+    match method {
+        "get" => {
+            let inputs: TodoServiceGetInput = serde_json::from_value(params).map_err(DispatchError::InvalidParams)?;
+            let output = provider.get(inputs.id).await.map_err(DispatchError::Provider)?;
+            serde_json::to_value(output).map_err(DispatchError::InvalidOutput)
+        }
+        "getAll" => {
+            let inputs: TodoServiceGetAllInput = serde_json::from_value(params).map_err(DispatchError::InvalidParams)?;
+            let output = provider.get_all(inputs.cursor).await.map_err(DispatchError::Provider)?;
+            serde_json::to_value(output).map_err(DispatchError::InvalidOutput)
+        }
+        "close" => {
+            let inputs: TodoServiceCloseInput = serde_json::from_value(params).map_err(DispatchError::InvalidParams)?;
+            let output = provider.close(inputs.id).await.map_err(DispatchError::Provider)?;
+            serde_json::to_value(output).map_err(DispatchError::InvalidOutput)
+        }
+        _ => Err(DispatchError::UnknownMethod(method.to_owned())),
+    }
+}
+
+/// Answers a request to `TodoService`, turning errors into error responses
+pub async fn handle_todo_service<P: TodoServiceProvider>(provider: &P, request: RpcRequest) -> RpcResponse
+where
+    P::Error: std::fmt::Display,
+{
+    let outcome = if request.version != RPC_VERSION {
+        let message = format!("unsupported version {}", request.version);
+        Err(RpcError::new("unsupported_version", message))
+    } else if request.service != "TodoService" {
+        let message = format!("unknown service `{}`", request.service);
+        Err(RpcError::new("unknown_service", message))
+    } else {
+        dispatch_todo_service(provider, &request.method, request.params)
+            .await
+            .map_err(RpcError::from)
+    };
+
+    RpcResponse::new(request.id, outcome)
+}
+
+pub struct TodoServiceClient<T> {
+    pub transport: T,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+impl<T: Transport> TodoServiceClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            next_id: Default::default(),
+        }
+    }
+
+    async fn request<O: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: impl Serialize,
+    ) -> Result<O, T::Error> {
+        let id = self.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let request = RpcRequest {
+            version: RPC_VERSION,
+            id: id.into(),
+            service: "TodoService".to_owned(),
+            method: method.to_owned(),
+            params: serde_json::to_value(params)?,
+        };
+
+        match self.transport.send(request).await?.outcome {
+            RpcOutcome::Result(result) => Ok(serde_json::from_value(result)?),
+            RpcOutcome::Error(error) => Err(error.into()),
+        }
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<Todo>, T::Error> {
+        let inputs = TodoServiceGetInput { id };
+        self.request("get", inputs).await
+    }
+
+    pub async fn get_all(&self, cursor: Option<String>) -> Result<Page<Todo>, T::Error> {
+        let inputs = TodoServiceGetAllInput { cursor };
+        self.request("getAll", inputs).await
+    }
+
+    pub async fn close(&self, id: i64) -> Result<(), T::Error> {
+        let inputs = TodoServiceCloseInput { id };
+        self.request("close", inputs).await
+    }
+}
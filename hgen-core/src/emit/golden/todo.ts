@@ -0,0 +1,87 @@
+// AUTOGENERATED FILE - DO NOT EDIT
+
+import { RpcError, RpcRequest, RpcResponse, requireParams, respond } from './rpc';
+
+export type Counts = Map<string, number>;
+
+export class Page<T> {
+  items: (T[]);
+  next: (string | null);
+}
+
+export class Todo {
+  id: bigint;
+  title: string;
+  tags: (string[]);
+  counts: Counts;
+  note: (string | null);
+  status: Status;
+}
+
+export enum Status {
+  Open = 'Open',
+  Done = 'Done',
+}
+
+export type Event =
+  | { type: 'Created'; value: Todo }
+  | { type: 'Closed'; value: { id: bigint } }
+  | { type: 'Cleared' };
+
+export class TodoServiceConsumer {
+  private id = 0;
+
+  constructor(
+    protected send: (request: RpcRequest) => Promise<RpcResponse>
+  ) {}
+
+  protected async request(method: string, params: RpcRequest['params']): Promise<any> {
+    const response = await this.send({ version: 1, id: ++this.id, service: "TodoService", method, params });
+    if ('error' in response) {
+      throw new RpcError(response.error.code, response.error.message);
+    }
+    return response.result;
+  }
+
+  get(id: bigint): Promise<(Todo | null)> {
+    return this.request("get", { id });
+  }
+
+  getAll(cursor: (string | null)): Promise<Page<Todo>> {
+    return this.request("getAll", { cursor });
+  }
+
+  close(id: bigint): Promise<void> {
+    return this.request("close", { id });
+  }
+}
+
+export interface TodoServiceProvider {
+  get(id: bigint): Promise<(Todo | null)>;
+
+  getAll(cursor: (string | null)): Promise<Page<Todo>>;
+
+  close(id: bigint): Promise<void>;
+}
+
+export function createTodoServiceDispatcher(provider: TodoServiceProvider) {
+  const dispatch = (method: string, params: any): Promise<any> => {
+    switch (method) {
+      case "get":
+        requireParams(params, ["id"]);
+        return provider.get(params.id);
+      case "getAll":
+        return provider.getAll(params.cursor);
+      case "close":
+        requireParams(params, ["id"]);
+        return provider.close(params.id);
+      default:
+        throw new RpcError('unknown_method', `unknown method ${method} of TodoService`);
+    }
+  };
+
+  return (request: RpcRequest): Promise<RpcResponse> => respond("TodoService", request, dispatch);
+}
+
+// prettier-ignore
+export const $schema = {models:{Counts:{type:'alias',inner:{type:'map',key:{type:'string'},value:{type:'int32'},metadata:{}}},Page:{type:'struct',typeParameters:['T'],fields:{items:{type:'list',inner:{type:'reference',name:'T'},metadata:{}},next:{type:'nullable',inner:{type:'string'},metadata:{}}}},Todo:{type:'struct',fields:{id:{type:'int64',metadata:{}},title:{type:'string',metadata:{minLength:1}},tags:{type:'list',inner:{type:'string'},metadata:{}},counts:{type:'reference',name:'Counts',metadata:{}},note:{type:'nullable',inner:{type:'string'},metadata:{}},status:{type:'reference',name:'Status',metadata:{}}}},Status:{type:'enum',fields:{Open:'',Done:''}},Event:{type:'union',tag:'type',content:'value',variants:{Created:{type:'tuple',inner:{type:'reference',name:'Todo'}},Closed:{type:'struct',fields:{id:{type:'int64',metadata:{}}}},Cleared:{type:'unit'}},metadata:{}}},services:{TodoService:{type:'service',methods:{get:{inputs:{id:{type:'int64'}},output:{type:'nullable',inner:{type:'reference',name:'Todo'}},metadata:{}},getAll:{inputs:{cursor:{type:'nullable',inner:{type:'string'}}},output:{type:'reference',name:'Page',arguments:[{type:'reference',name:'Todo'}]},metadata:{}},close:{inputs:{id:{type:'int64'}},output:{},metadata:{}}}}}} as const;
\ No newline at end of file
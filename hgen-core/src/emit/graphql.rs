@@ -0,0 +1,384 @@
+//! Emits a schema as GraphQL SDL, for gateways serving the same models.
+//!
+//! Every struct becomes an object `type` along with a matching `input` type,
+//! and unions become a GraphQL union of one object type per variant, along
+//! with a `@oneOf` input. Aliases are replaced by their shape, and generic
+//! structs are emitted once per use, e.g. `Page<Todo>` as `PageTodo`.
+//!
+//! Service methods become fields of `Query` or `Mutation`, as chosen by their
+//! `operation` metadata. Methods without one fall back to the verb of their
+//! `rest` metadata, where `GET` and `HEAD` are queries, and are mutations
+//! otherwise:
+//!
+//! ```text
+//! getAll() -> List<Todo> { operation: "query" }
+//! ```
+//!
+//! Fields are prefixed with the name of their service, without its `Service`
+//! suffix, so `TodoService.getAll` becomes `todoGetAll`.
+
+use super::{resolve, substitute, type_name, ToPascalCase};
+use crate::lang::{loader::Module, map::OrderedHashMap, schema::*};
+use serde_json::{Map, Value};
+
+/// Metadata key choosing between `query` and `mutation`
+const OPERATION: &str = "operation";
+
+/// Emits the services of the first module, along with the models of all
+/// modules. `Int64` and `Map` have no GraphQL equivalent and are emitted as
+/// custom scalars, named through the `scalars` option, e.g.
+/// `{ Int64 = "BigInt", Map = "JSON" }`.
+pub fn emit_schema(modules: &[Module], options: &Map<String, Value>) -> String {
+    let schema = &modules[0].schema;
+
+    let mut models = OrderedHashMap::new();
+    for module in modules.iter().rev() {
+        for (name, model) in module.schema.models.iter() {
+            models.insert(*name, model);
+        }
+    }
+
+    let scalars = options.get("scalars").and_then(Value::as_object);
+    let scalar = |key: &str, default: &str| {
+        scalars
+            .and_then(|scalars| scalars.get(key))
+            .and_then(Value::as_str)
+            .unwrap_or(default)
+            .to_owned()
+    };
+
+    let mut emitter = Emitter {
+        models,
+        int64_scalar: scalar("Int64", "Int64"),
+        map_scalar: scalar("Map", "JSON"),
+        scalars: Vec::new(),
+        uses_one_of: false,
+        synthesized: Vec::new(),
+        instantiated: Vec::new(),
+    };
+
+    // emit models, including imported ones since SDL has no imports
+    let mut body = Vec::new();
+    let mut emitted = Vec::new();
+    for module in modules.iter() {
+        for (name, def) in module.schema.models.iter() {
+            if !emitted.contains(name) {
+                emitted.push(*name);
+                body.extend(emitter.emit_model(name, def));
+            }
+        }
+    }
+
+    // emit operations
+    let mut queries = String::new();
+    let mut mutations = String::new();
+    for (service_name, service) in schema.services.iter() {
+        let prefix = to_camel_case(
+            service_name
+                .strip_suffix("Service")
+                .filter(|prefix| !prefix.is_empty())
+                .unwrap_or(service_name),
+        );
+        for (name, method) in service.methods.iter() {
+            let name = format!("{}{}", prefix, name.to_pascal_case());
+            let field = emitter.emit_operation(&name, method);
+            match is_query(method) {
+                true => queries.push_str(&field),
+                false => mutations.push_str(&field),
+            }
+        }
+    }
+    // a schema is only valid with a query type, which needs at least one field
+    if queries.is_empty() {
+        queries.push_str("  \"Placeholder, since the schema has no queries\"\n");
+        queries.push_str("  _empty: Boolean\n");
+    }
+    body.push(format!("type Query {{\n{}}}\n", queries));
+    if !mutations.is_empty() {
+        body.push(format!("type Mutation {{\n{}}}\n", mutations));
+    }
+
+    body.extend(emitter.synthesized);
+
+    let mut output = String::new();
+
+    // emit header
+    output.push_str("# AUTOGENERATED FILE - DO NOT EDIT\n");
+    output.push('\n');
+
+    // emit scalars
+    if !emitter.scalars.is_empty() {
+        for scalar in emitter.scalars.iter() {
+            output.push_str(&format!("scalar {}\n", scalar));
+        }
+        output.push('\n');
+    }
+
+    // emit directives, which not all servers know already
+    if emitter.uses_one_of {
+        output.push_str("directive @oneOf on INPUT_OBJECT\n");
+        output.push('\n');
+    }
+
+    output.push_str(&body.join("\n"));
+
+    output
+}
+
+/// Whether a method is a query rather than a mutation
+fn is_query(method: &Annotated<ServiceMethod>) -> bool {
+    if let Some(Literal::String(operation)) = method.metadata.get(OPERATION) {
+        return operation.eq_ignore_ascii_case("query");
+    }
+
+    match method.metadata.get("rest") {
+        Some(Literal::Object(rest)) => matches!(
+            rest.get("method"),
+            Some(Literal::String(verb))
+                if verb.eq_ignore_ascii_case("GET") || verb.eq_ignore_ascii_case("HEAD")
+        ),
+        _ => false,
+    }
+}
+
+struct Emitter<'a> {
+    models: OrderedHashMap<&'a str, &'a Model<'a>>,
+    int64_scalar: String,
+    map_scalar: String,
+    /// Custom scalars used by the schema, declared at the top of the file
+    scalars: Vec<String>,
+    /// Whether a union input needs the `@oneOf` directive to be declared
+    uses_one_of: bool,
+    /// Types for generic structs, emitted after the models and operations
+    synthesized: Vec<String>,
+    /// Names of all synthesized types, so that each is emitted only once
+    instantiated: Vec<String>,
+}
+
+impl<'a> Emitter<'a> {
+    /// Emits structs, enums and unions, while aliases, externals and generic
+    /// structs are emitted wherever they are used
+    fn emit_model(&mut self, name: &str, def: &'a Model<'a>) -> Option<String> {
+        match def {
+            Model::Struct(inner) if inner.type_parameters.is_empty() => {
+                Some(self.emit_struct(name, &inner.fields, &[]))
+            }
+            Model::Enum(inner) => Some(emit_enum(name, inner)),
+            Model::Union(inner) => Some(self.emit_union(name, inner)),
+            _ => None,
+        }
+    }
+
+    /// Emits an object type along with its matching input type
+    fn emit_struct(
+        &mut self,
+        name: &str,
+        fields: &OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>,
+        bindings: &[(&'a str, Shape<'a>)],
+    ) -> String {
+        let mut output = String::new();
+
+        output.push_str(&format!("type {} {{\n", name));
+        output.push_str(&self.emit_fields(fields, bindings, false));
+        output.push_str("}\n");
+        output.push('\n');
+        output.push_str(&format!("input {}Input {{\n", name));
+        output.push_str(&self.emit_fields(fields, bindings, true));
+        output.push_str("}\n");
+
+        output
+    }
+
+    fn emit_fields(
+        &mut self,
+        fields: &OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>,
+        bindings: &[(&'a str, Shape<'a>)],
+        is_input: bool,
+    ) -> String {
+        let mut output = String::new();
+        for (name, field) in fields.iter() {
+            let shape = substitute(&field.inner, bindings);
+            output.push_str(&format!("  {}: {}\n", name, self.emit_type(&shape, is_input)));
+        }
+        output
+    }
+
+    fn emit_union(&mut self, name: &str, def: &'a Union<'a>) -> String {
+        let mut output = String::new();
+        let mut types = Vec::new();
+        let mut inputs = String::new();
+
+        for (variant_name, variant) in def.variants.iter() {
+            let type_name = format!("{}{}", name, variant_name);
+
+            output.push_str(&format!("type {} {{\n", type_name));
+            output.push_str(&format!("  {}: String!\n", def.tag()));
+            let input = match variant {
+                Variant::Unit => "Boolean".to_owned(),
+                Variant::Tuple(shape) => {
+                    output.push_str(&format!(
+                        "  {}: {}\n",
+                        def.content(),
+                        self.emit_type(shape, false)
+                    ));
+                    self.emit_type(shape, true).trim_end_matches('!').to_owned()
+                }
+                Variant::Struct(fields) => {
+                    output.push_str(&self.emit_fields(fields, &[], false));
+                    let input = self.emit_fields(fields, &[], true);
+                    self.synthesized
+                        .push(format!("input {}Input {{\n{}}}\n", type_name, input));
+                    format!("{}Input", type_name)
+                }
+            };
+            output.push_str("}\n");
+            output.push('\n');
+
+            types.push(type_name);
+            inputs.push_str(&format!("  {}: {}\n", to_camel_case(variant_name), input));
+        }
+
+        output.push_str(&format!("union {} = {}\n", name, types.join(" | ")));
+        output.push('\n');
+        self.uses_one_of = true;
+        output.push_str(&format!("input {}Input @oneOf {{\n", name));
+        output.push_str(&inputs);
+        output.push_str("}\n");
+
+        output
+    }
+
+    fn emit_operation(&mut self, name: &str, method: &'a Annotated<ServiceMethod<'a>>) -> String {
+        let mut output = String::new();
+
+        if let Some(Literal::String(description)) = method.metadata.get("description") {
+            output.push_str(&format!(
+                "  {}\n",
+                Value::String(unescape(description))
+            ));
+        }
+
+        let inputs = method
+            .inner
+            .inputs
+            .iter()
+            .map(|(name, shape)| format!("{}: {}", name, self.emit_type(shape, true)))
+            .collect::<Vec<_>>();
+        let output_type = match &method.inner.output {
+            Some(shape) => self.emit_type(shape, false),
+            // fields always have a type, so methods without output resolve to null
+            None => "Boolean".to_owned(),
+        };
+
+        match inputs.is_empty() {
+            true => output.push_str(&format!("  {}: {}\n", name, output_type)),
+            false => output.push_str(&format!(
+                "  {}({}): {}\n",
+                name,
+                inputs.join(", "),
+                output_type
+            )),
+        }
+
+        output
+    }
+
+    /// Emits a type reference, which is non-null unless the shape is nullable
+    fn emit_type(&mut self, shape: &Shape<'a>, is_input: bool) -> String {
+        match resolve(&self.models, shape) {
+            Shape::Nullable(inner) => self.emit_nullable_type(&inner, is_input),
+            shape => format!("{}!", self.emit_nullable_type(&shape, is_input)),
+        }
+    }
+
+    fn emit_nullable_type(&mut self, shape: &Shape<'a>, is_input: bool) -> String {
+        match resolve(&self.models, shape) {
+            Shape::Bool => "Boolean".to_owned(),
+            Shape::Int8 | Shape::Int16 | Shape::Int32 => "Int".to_owned(),
+            Shape::Int64 => self.use_scalar(self.int64_scalar.clone()),
+            Shape::Float32 | Shape::Float64 => "Float".to_owned(),
+            Shape::String => "String".to_owned(),
+            Shape::List(inner) => format!("[{}]", self.emit_type(&inner, is_input)),
+            Shape::Map(..) => self.use_scalar(self.map_scalar.clone()),
+            Shape::Nullable(inner) => self.emit_nullable_type(&inner, is_input),
+            Shape::Reference(name, arguments) => {
+                let name = match arguments.is_empty() {
+                    true => name.to_owned(),
+                    false => self.instantiate(name, &arguments),
+                };
+                match is_input && !matches!(self.models.get(name.as_str()), Some(Model::Enum(_))) {
+                    true => format!("{}Input", name),
+                    false => name,
+                }
+            }
+        }
+    }
+
+    fn use_scalar(&mut self, scalar: String) -> String {
+        if !self.scalars.contains(&scalar) {
+            self.scalars.push(scalar.clone());
+        }
+        scalar
+    }
+
+    /// Emits the types of a generic struct with the given type arguments,
+    /// returning their name
+    fn instantiate(&mut self, name: &'a str, arguments: &[Annotated<'a, Shape<'a>>]) -> String {
+        let arguments = arguments
+            .iter()
+            .map(|argument| resolve(&self.models, argument))
+            .collect::<Vec<_>>();
+        let type_name = type_name(&Shape::Reference(
+            name,
+            arguments.iter().cloned().map(Annotated::new).collect(),
+        ));
+
+        if !self.instantiated.contains(&type_name) {
+            self.instantiated.push(type_name.clone());
+            if let Some(Model::Struct(inner)) = self.models.get(name).copied() {
+                let bindings = inner
+                    .type_parameters
+                    .iter()
+                    .copied()
+                    .zip(arguments)
+                    .collect::<Vec<_>>();
+                let types = self.emit_struct(&type_name, &inner.fields, &bindings);
+                self.synthesized.push(types);
+            }
+        }
+
+        type_name
+    }
+}
+
+fn emit_enum(name: &str, def: &Enum) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("enum {} {{\n", name));
+    for field in def.fields.iter() {
+        output.push_str(&format!("  {}\n", field));
+    }
+    output.push_str("}\n");
+
+    output
+}
+
+fn to_camel_case(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let module = module(SCHEMA);
+        assert_golden("todo.graphql", &emit_schema(&[module], &Map::new()));
+    }
+}
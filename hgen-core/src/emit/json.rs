@@ -0,0 +1,189 @@
+//! Emits a schema as a stable JSON document, so tooling outside of this crate
+//! can consume hGEN schemas. The layout is described in the README and
+//! versioned through [`VERSION`], which is bumped on every breaking change.
+
+use crate::lang::{map::OrderedHashMap, schema::*};
+use serde_json::{json, Map, Value};
+
+pub const VERSION: u32 = 1;
+
+pub fn emit_schema(name: &str, schema: &Schema) -> String {
+    let mut output = serde_json::to_string_pretty(&reflect_schema(name, schema)).unwrap();
+    output.push('\n');
+    output
+}
+
+pub fn reflect_schema(name: &str, schema: &Schema) -> Value {
+    json!({
+        "version": VERSION,
+        "name": name,
+        "imports": schema.imports.iter().map(reflect_import).collect::<Vec<_>>(),
+        "models": schema
+            .models
+            .iter()
+            .map(|(name, def)| reflect_model(name, def))
+            .collect::<Vec<_>>(),
+        "services": schema
+            .services
+            .iter()
+            .map(|(name, def)| reflect_service(name, def))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn reflect_import(import: &Import) -> Value {
+    json!({
+        "path": import.path,
+        "names": import.names,
+        "wildcard": import.is_wildcard,
+    })
+}
+
+fn reflect_model(name: &str, def: &Model) -> Value {
+    match def {
+        Model::Struct(inner) => json!({
+            "name": name,
+            "type": "struct",
+            "typeParameters": inner.type_parameters,
+            "fields": reflect_fields(&inner.fields),
+        }),
+        Model::Enum(inner) => json!({
+            "name": name,
+            "type": "enum",
+            "variants": inner.fields,
+        }),
+        Model::Union(inner) => json!({
+            "name": name,
+            "type": "union",
+            "tag": inner.tag(),
+            "content": inner.content(),
+            "variants": inner
+                .variants
+                .iter()
+                .map(|(name, variant)| reflect_variant(name, variant))
+                .collect::<Vec<_>>(),
+            "metadata": reflect_metadata(&inner.metadata),
+        }),
+        Model::Alias(inner) => json!({
+            "name": name,
+            "type": "alias",
+            "typeParameters": inner.type_parameters,
+            "shape": reflect_shape(&inner.shape.inner),
+            "metadata": reflect_metadata(&inner.shape.metadata),
+        }),
+        Model::External(inner) => json!({
+            "name": name,
+            "type": "external",
+            "shape": reflect_shape(&inner.shape.inner),
+            "metadata": reflect_metadata(&inner.shape.metadata),
+        }),
+    }
+}
+
+fn reflect_variant(name: &str, variant: &Variant) -> Value {
+    match variant {
+        Variant::Unit => json!({ "name": name, "type": "unit" }),
+        Variant::Tuple(shape) => json!({
+            "name": name,
+            "type": "tuple",
+            "shape": reflect_shape(shape),
+        }),
+        Variant::Struct(fields) => json!({
+            "name": name,
+            "type": "struct",
+            "fields": reflect_fields(fields),
+        }),
+    }
+}
+
+fn reflect_fields(fields: &OrderedHashMap<&str, Annotated<Shape>>) -> Value {
+    fields
+        .iter()
+        .map(|(name, shape)| {
+            json!({
+                "name": name,
+                "shape": reflect_shape(&shape.inner),
+                "metadata": reflect_metadata(&shape.metadata),
+            })
+        })
+        .collect()
+}
+
+fn reflect_service(name: &str, def: &Service) -> Value {
+    json!({
+        "name": name,
+        "methods": def
+            .methods
+            .iter()
+            .map(|(name, method)| json!({
+                "name": name,
+                "inputs": method
+                    .inner
+                    .inputs
+                    .iter()
+                    .map(|(name, shape)| json!({ "name": name, "shape": reflect_shape(shape) }))
+                    .collect::<Vec<_>>(),
+                "output": method.inner.output.as_ref().map(|shape| reflect_shape(shape)),
+                "metadata": reflect_metadata(&method.metadata),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn reflect_shape(shape: &Shape) -> Value {
+    match shape {
+        Shape::Bool => json!({ "type": "bool" }),
+        Shape::Int8 => json!({ "type": "int8" }),
+        Shape::Int16 => json!({ "type": "int16" }),
+        Shape::Int32 => json!({ "type": "int32" }),
+        Shape::Int64 => json!({ "type": "int64" }),
+        Shape::Float32 => json!({ "type": "float32" }),
+        Shape::Float64 => json!({ "type": "float64" }),
+        Shape::String => json!({ "type": "string" }),
+        Shape::Nullable(inner) => json!({ "type": "nullable", "inner": reflect_shape(inner) }),
+        Shape::List(inner) => json!({ "type": "list", "inner": reflect_shape(inner) }),
+        Shape::Map(key, value) => json!({
+            "type": "map",
+            "key": reflect_shape(key),
+            "value": reflect_shape(value),
+        }),
+        Shape::Reference(name, arguments) => json!({
+            "type": "reference",
+            "name": name,
+            "arguments": arguments.iter().map(|argument| reflect_shape(argument)).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn reflect_metadata(metadata: &OrderedHashMap<&str, Literal>) -> Value {
+    Value::Object(
+        metadata
+            .iter()
+            .map(|(key, value)| (key.to_string(), reflect_literal(value)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+fn reflect_literal(literal: &Literal) -> Value {
+    match literal {
+        Literal::Bool(inner) => json!(inner),
+        Literal::Int(inner) => json!(inner),
+        Literal::Float(inner) => json!(inner),
+        Literal::Char(inner) => json!(inner.to_string()),
+        Literal::String(inner) => json!(unescape(inner)),
+        Literal::Array(inner) => inner.iter().map(reflect_literal).collect(),
+        Literal::Object(inner) => reflect_metadata(inner),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let module = module(SCHEMA);
+        assert_golden("todo.json", &emit_schema("todo", &module.schema));
+    }
+}
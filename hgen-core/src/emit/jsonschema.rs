@@ -0,0 +1,238 @@
+//! Emits the models of a schema as a JSON Schema (2020-12) document, with one
+//! entry in `$defs` per model. The conversion of models into schemas is shared
+//! with the OpenAPI emitter, whose component schemas use the same dialect.
+//!
+//! The `checks` metadata of shapes is translated into validation keywords:
+//!
+//! ```text
+//! alias Name = String & {
+//!   checks: [
+//!     { type: "length", min: 3, max: 20 },
+//!     { type: "regex", pattern: "^[A-Z]" },
+//!   ]
+//! };
+//! ```
+//!
+//! `length` becomes `minLength` and `maxLength` (or `minItems` and `maxItems`
+//! for lists), and `regex` becomes `pattern`. Other checks have no equivalent
+//! and are left out.
+
+use crate::lang::{loader::Module, map::OrderedHashMap, schema::*};
+use serde_json::{json, Map, Value};
+
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Generic models are expanded wherever they are used, which would never end
+/// for recursive ones, so expansion gives up at this depth
+const MAX_DEPTH: usize = 8;
+
+/// Emits the models of all modules, so references to imported models resolve
+/// within the document. The `id` option sets the `$id` of the document.
+pub fn emit_schema(name: &str, modules: &[Module], options: &Map<String, Value>) -> String {
+    let mut output = serde_json::to_string_pretty(&reflect_document(name, modules, options)).unwrap();
+    output.push('\n');
+    output
+}
+
+fn reflect_document(name: &str, modules: &[Module], options: &Map<String, Value>) -> Value {
+    let converter = Converter::new(modules, "#/$defs/");
+
+    let mut document = json!({ "$schema": DIALECT });
+    if let Some(id) = options.get("id").and_then(Value::as_str) {
+        document["$id"] = json!(id);
+    }
+    document["title"] = json!(name);
+    document["$defs"] = Value::Object(converter.definitions());
+
+    document
+}
+
+pub(crate) struct Converter<'a> {
+    models: OrderedHashMap<&'a str, &'a Model<'a>>,
+    /// Prefix of the location of non-generic models, which references point to
+    prefix: &'static str,
+}
+
+impl<'a> Converter<'a> {
+    /// Collects the models of all modules, with the models of the first module
+    /// taking precedence
+    pub(crate) fn new(modules: &'a [Module<'a>], prefix: &'static str) -> Self {
+        let mut models = OrderedHashMap::new();
+        for module in modules.iter().rev() {
+            for (name, model) in module.schema.models.iter() {
+                models.insert(*name, model);
+            }
+        }
+
+        Converter { models, prefix }
+    }
+
+    /// Converts every model which is not generic, keyed by name
+    pub(crate) fn definitions(&self) -> Map<String, Value> {
+        let mut definitions = Map::new();
+        for (name, model) in self.models.iter() {
+            // generic models are expanded wherever they are used instead
+            if model.type_parameters().is_empty() {
+                definitions.insert(name.to_string(), self.model(model, &[], 0));
+            }
+        }
+        definitions
+    }
+
+    /// Converts a model, with its type parameters bound to `arguments`
+    fn model(&self, model: &Model, arguments: &[(&str, Value)], depth: usize) -> Value {
+        match model {
+            Model::Struct(inner) => self.fields(&inner.fields, arguments, depth),
+            Model::Enum(inner) => json!({ "type": "string", "enum": inner.fields }),
+            Model::Union(inner) => {
+                let variants = inner
+                    .variants
+                    .iter()
+                    .map(|(name, variant)| {
+                        let mut properties = Map::new();
+                        properties.insert(inner.tag().to_owned(), json!({ "const": name }));
+                        let content = match variant {
+                            Variant::Unit => None,
+                            Variant::Tuple(shape) => Some(self.shape(shape, arguments, depth)),
+                            Variant::Struct(fields) => Some(self.fields(fields, arguments, depth)),
+                        };
+
+                        let mut required = vec![inner.tag()];
+                        if let Some(content) = content {
+                            properties.insert(inner.content().to_owned(), content);
+                            required.push(inner.content());
+                        }
+                        object(properties, required)
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "oneOf": variants })
+            }
+            Model::Alias(inner) => self.annotated(&inner.shape, arguments, depth),
+            Model::External(inner) => self.annotated(&inner.shape, arguments, depth),
+        }
+    }
+
+    /// Converts fields into an object, requiring all fields which are not nullable
+    fn fields(
+        &self,
+        fields: &OrderedHashMap<&str, Annotated<Shape>>,
+        arguments: &[(&str, Value)],
+        depth: usize,
+    ) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for (name, shape) in fields.iter() {
+            properties.insert(name.to_string(), self.annotated(shape, arguments, depth));
+            if !matches!(shape.inner, Shape::Nullable(_)) {
+                required.push(*name);
+            }
+        }
+        object(properties, required)
+    }
+
+    /// Converts a shape along with the checks of its metadata
+    fn annotated(&self, shape: &Annotated<Shape>, arguments: &[(&str, Value)], depth: usize) -> Value {
+        let mut schema = self.shape(&shape.inner, arguments, depth);
+        let Some(Literal::Array(checks)) = shape.metadata.get("checks") else {
+            return schema;
+        };
+
+        let is_list = match &shape.inner {
+            Shape::Nullable(inner) => matches!(**inner, Shape::List(_)),
+            inner => matches!(inner, Shape::List(_)),
+        };
+
+        for check in checks {
+            let Literal::Object(check) = check else {
+                continue;
+            };
+            match check.get("type") {
+                Some(Literal::String("length")) => {
+                    let (min, max) = match is_list {
+                        true => ("minItems", "maxItems"),
+                        false => ("minLength", "maxLength"),
+                    };
+                    if let Some(Literal::Int(value)) = check.get("min") {
+                        schema[min] = json!(value);
+                    }
+                    if let Some(Literal::Int(value)) = check.get("max") {
+                        schema[max] = json!(value);
+                    }
+                }
+                Some(Literal::String("regex")) => {
+                    if let Some(Literal::String(pattern)) = check.get("pattern") {
+                        schema["pattern"] = json!(unescape(pattern));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        schema
+    }
+
+    /// Converts a shape, with type parameters bound to `arguments`
+    pub(crate) fn shape(&self, shape: &Shape, arguments: &[(&str, Value)], depth: usize) -> Value {
+        match shape {
+            Shape::Bool => json!({ "type": "boolean" }),
+            Shape::Int8 => json!({ "type": "integer", "minimum": i8::MIN, "maximum": i8::MAX }),
+            Shape::Int16 => json!({ "type": "integer", "minimum": i16::MIN, "maximum": i16::MAX }),
+            Shape::Int32 => json!({ "type": "integer", "format": "int32" }),
+            Shape::Int64 => json!({ "type": "integer", "format": "int64" }),
+            Shape::Float32 => json!({ "type": "number", "format": "float" }),
+            Shape::Float64 => json!({ "type": "number", "format": "double" }),
+            Shape::String => json!({ "type": "string" }),
+            Shape::List(inner) => json!({
+                "type": "array",
+                "items": self.shape(inner, arguments, depth),
+            }),
+            // JSON only has string keys, so the key shape is not represented
+            Shape::Map(_, value) => json!({
+                "type": "object",
+                "additionalProperties": self.shape(value, arguments, depth),
+            }),
+            Shape::Nullable(inner) => json!({
+                "anyOf": [self.shape(inner, arguments, depth), { "type": "null" }],
+            }),
+            Shape::Reference(name, _) if arguments.iter().any(|(parameter, _)| parameter == name) => {
+                let (_, value) = arguments.iter().find(|(parameter, _)| parameter == name).unwrap();
+                value.clone()
+            }
+            Shape::Reference(name, shapes) if shapes.is_empty() => {
+                json!({ "$ref": format!("{}{}", self.prefix, name) })
+            }
+            Shape::Reference(name, shapes) => match self.models.get(name) {
+                Some(model) if depth < MAX_DEPTH => {
+                    let arguments = model
+                        .type_parameters()
+                        .iter()
+                        .copied()
+                        .zip(shapes.iter().map(|shape| self.shape(shape, arguments, depth)))
+                        .collect::<Vec<_>>();
+                    self.model(model, &arguments, depth + 1)
+                }
+                _ => json!({}),
+            },
+        }
+    }
+}
+
+pub(crate) fn object(properties: Map<String, Value>, required: Vec<&str>) -> Value {
+    let mut object = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        object["required"] = json!(required);
+    }
+    object
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let module = module(SCHEMA);
+        assert_golden("todo.schema.json", &emit_schema("todo", &[module], &Map::new()));
+    }
+}
@@ -0,0 +1,163 @@
+//! Emitters of all targets, along with helpers shared between them.
+
+pub mod dart;
+pub mod graphql;
+pub mod json;
+pub mod jsonschema;
+pub mod openapi;
+pub mod plugin;
+pub mod proto;
+pub mod py;
+pub mod rpc;
+pub mod rs;
+pub mod ts;
+pub mod yaml;
+
+use crate::lang::{map::OrderedHashMap, schema::*};
+
+/// Replaces aliases and externals by their shape, until the shape is no longer
+/// a reference to one of them
+pub(crate) fn resolve<'a>(
+    models: &OrderedHashMap<&'a str, &'a Model<'a>>,
+    shape: &Shape<'a>,
+) -> Shape<'a> {
+    let Shape::Reference(name, arguments) = shape else {
+        return shape.clone();
+    };
+
+    match models.get(name) {
+        Some(Model::Alias(inner)) => {
+            let bindings = inner
+                .type_parameters
+                .iter()
+                .copied()
+                .zip(arguments.iter().map(|argument| argument.inner.clone()))
+                .collect::<Vec<_>>();
+            resolve(models, &substitute(&inner.shape.inner, &bindings))
+        }
+        Some(Model::External(inner)) => resolve(models, &inner.shape.inner),
+        _ => shape.clone(),
+    }
+}
+
+/// Binds type parameters within a shape to their arguments
+pub(crate) fn substitute<'a>(shape: &Shape<'a>, bindings: &[(&'a str, Shape<'a>)]) -> Shape<'a> {
+    match shape {
+        Shape::List(inner) => Shape::List(Box::new(inner.map(|inner| substitute(inner, bindings)))),
+        Shape::Map(key, value) => Shape::Map(
+            Box::new(key.map(|key| substitute(key, bindings))),
+            Box::new(value.map(|value| substitute(value, bindings))),
+        ),
+        Shape::Nullable(inner) => Shape::Nullable(Box::new(substitute(inner, bindings))),
+        Shape::Reference(name, arguments) => {
+            match bindings.iter().find(|(parameter, _)| parameter == name) {
+                Some((_, argument)) => argument.clone(),
+                None => Shape::Reference(
+                    name,
+                    arguments
+                        .iter()
+                        .map(|argument| argument.map(|argument| substitute(argument, bindings)))
+                        .collect(),
+                ),
+            }
+        }
+        shape => shape.clone(),
+    }
+}
+
+/// Name of a type synthesized for the given shape, e.g. `PageTodo` for
+/// `Page<Todo>`
+pub(crate) fn type_name(shape: &Shape) -> String {
+    match shape {
+        Shape::List(inner) => format!("List{}", type_name(inner)),
+        Shape::Map(key, value) => format!("Map{}{}", type_name(key), type_name(value)),
+        Shape::Nullable(inner) => format!("Nullable{}", type_name(inner)),
+        Shape::Reference(name, arguments) => {
+            let mut output = name.to_string();
+            for argument in arguments {
+                output.push_str(&type_name(argument));
+            }
+            output
+        }
+        shape => shape.to_string(),
+    }
+}
+
+pub trait ToSnakeCase {
+    fn to_snake_case(&self) -> String;
+}
+
+impl ToSnakeCase for str {
+    /// Splits words at lowercase to uppercase boundaries, keeping acronyms and
+    /// names which are uppercase already together, e.g. `userID` as `user_id`
+    fn to_snake_case(&self) -> String {
+        let mut output = String::new();
+        let mut previous: Option<char> = None;
+        for c in self.chars() {
+            if c.is_uppercase()
+                && previous
+                    .is_some_and(|previous| previous.is_lowercase() || previous.is_ascii_digit())
+            {
+                output.push('_');
+            }
+            output.extend(c.to_lowercase());
+            previous = Some(c);
+        }
+        output
+    }
+}
+
+impl ToSnakeCase for String {
+    fn to_snake_case(&self) -> String {
+        self.as_str().to_snake_case()
+    }
+}
+
+pub trait ToPascalCase {
+    fn to_pascal_case(&self) -> String;
+}
+
+impl ToPascalCase for str {
+    fn to_pascal_case(&self) -> String {
+        let mut chars = self.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::lang::{loader::Module, schema::Schema};
+    use std::{env, fs, path::Path};
+
+    /// Schema every emitter is tested with
+    pub const SCHEMA: &str = include_str!("golden/todo.hgen");
+
+    pub fn module(source: &str) -> Module<'_> {
+        Module {
+            path: Path::new("todo.hgen"),
+            source,
+            schema: Schema::parse(source).unwrap(),
+            dependencies: Vec::new(),
+            is_complete: true,
+        }
+    }
+
+    /// Compares output with the golden file of the given name, which is
+    /// rewritten instead when `UPDATE_GOLDEN` is set
+    pub fn assert_golden(name: &str, output: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/emit/golden")
+            .join(name);
+
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, output).unwrap();
+            return;
+        }
+
+        let golden = fs::read_to_string(&path).unwrap();
+        assert_eq!(output, golden, "output differs from {}", path.display());
+    }
+}
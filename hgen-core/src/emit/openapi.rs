@@ -0,0 +1,318 @@
+//! Emits an OpenAPI 3.1 document describing the REST API of all services.
+//! Operations are built from the metadata of service methods, so methods
+//! without `rest` metadata are left out:
+//!
+//! ```text
+//! getById(id: Id) -> Order {
+//!   rest: { method: "GET", uri: "/orders/{id}" },
+//!   auth: { scopes: ["orders:read"] },
+//!   name: "Get Order",
+//!   description: "Returns the order associated with the given id.",
+//! }
+//! ```
+//!
+//! Inputs named by a `{placeholder}` of the uri become path parameters. The
+//! remaining inputs are sent as query parameters for `GET`, `HEAD` and
+//! `DELETE`, and as properties of a JSON body otherwise. Methods sharing a
+//! route are reported by [`check`], since only one of them could be part of
+//! the document.
+
+use super::{
+    jsonschema::{object, Converter},
+    yaml,
+};
+use crate::lang::{
+    diagnostic::{Diagnostic, Span},
+    loader::Module,
+    map::OrderedHashMap,
+    schema::*,
+};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Name of the security scheme which the scopes of methods refer to
+const SECURITY_SCHEME: &str = "bearer";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    Yaml,
+}
+
+/// Emits the services of the first module, along with the models of all
+/// modules. The `title` and `version` options fill in the `info` object.
+pub fn emit_schema(
+    name: &str,
+    modules: &[Module],
+    options: &Map<String, Value>,
+    format: Format,
+) -> String {
+    let document = reflect_document(name, modules, options);
+
+    match format {
+        Format::Json => {
+            let mut output = serde_json::to_string_pretty(&document).unwrap();
+            output.push('\n');
+            output
+        }
+        Format::Yaml => yaml::to_yaml(&document),
+    }
+}
+
+/// Reports methods of the first module with the same verb and uri as an
+/// earlier method. Placeholders match regardless of their name, so
+/// `/orders/{id}` and `/orders/{orderId}` are the same route.
+pub fn check(modules: &[Module]) -> Vec<Diagnostic> {
+    let module = &modules[0];
+    let mut routes = HashMap::new();
+    let mut diagnostics = Vec::new();
+
+    for (service_name, service) in module.schema.services.iter() {
+        for (method_name, method) in service.methods.iter() {
+            let Some((verb, uri)) = route(method) else {
+                continue;
+            };
+
+            let key = (verb.to_uppercase(), path_template(uri));
+            match routes.get(&key) {
+                Some(other) => diagnostics.push(
+                    Diagnostic::error(
+                        Span::of(module.source, method_name),
+                        format!(
+                            "route {} {} of `{}.{}` is already used by `{}`",
+                            key.0, uri, service_name, method_name, other
+                        ),
+                    )
+                    .with_file(module.path),
+                ),
+                None => {
+                    routes.insert(key, format!("{}.{}", service_name, method_name));
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn reflect_document(name: &str, modules: &[Module], options: &Map<String, Value>) -> Value {
+    // imported models are part of the document as well
+    let converter = Converter::new(modules, "#/components/schemas/");
+
+    let mut paths = Map::new();
+    let mut is_secured = false;
+    for (service_name, service) in modules[0].schema.services.iter() {
+        for (method_name, method) in service.methods.iter() {
+            let Some((verb, uri)) = route(method) else {
+                continue;
+            };
+
+            let operation = reflect_operation(&converter, service_name, method_name, method, verb, uri);
+            is_secured |= operation.get("security").is_some();
+
+            paths
+                .entry(uri.to_string())
+                .or_insert_with(|| json!({}))
+                .as_object_mut()
+                .unwrap()
+                .insert(verb.to_lowercase(), operation);
+        }
+    }
+
+    let mut components = json!({ "schemas": converter.definitions() });
+    if is_secured {
+        components["securitySchemes"] = json!({
+            SECURITY_SCHEME: { "type": "http", "scheme": "bearer" },
+        });
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": options.get("title").and_then(Value::as_str).unwrap_or(name),
+            "version": options.get("version").and_then(Value::as_str).unwrap_or("1.0.0"),
+        },
+        "paths": paths,
+        "components": components,
+    })
+}
+
+fn reflect_operation(
+    converter: &Converter,
+    service_name: &str,
+    method_name: &str,
+    method: &Annotated<ServiceMethod>,
+    verb: &str,
+    uri: &str,
+) -> Value {
+    let mut operation = Map::new();
+    let placeholders = find_placeholders(uri);
+    let has_body = !matches!(verb.to_uppercase().as_str(), "GET" | "HEAD" | "DELETE");
+
+    operation.insert(
+        "operationId".to_owned(),
+        json!(format!("{}.{}", service_name, method_name)),
+    );
+    if let Some(summary) = metadata_string(&method.metadata, "name") {
+        operation.insert("summary".to_owned(), json!(summary));
+    }
+    if let Some(description) = metadata_string(&method.metadata, "description") {
+        operation.insert("description".to_owned(), json!(description));
+    }
+    operation.insert("tags".to_owned(), json!([service_name]));
+
+    let mut parameters = Vec::new();
+    for placeholder in placeholders.iter() {
+        let schema = match method.inner.inputs.get(placeholder) {
+            Some(shape) => converter.shape(shape, &[], 0),
+            None => json!({ "type": "string" }),
+        };
+        parameters.push(json!({
+            "name": placeholder,
+            "in": "path",
+            "required": true,
+            "schema": schema,
+        }));
+    }
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for (name, shape) in method.inner.inputs.iter() {
+        if placeholders.contains(name) {
+            continue;
+        }
+
+        let is_required = !matches!(shape.inner, Shape::Nullable(_));
+        if has_body {
+            properties.insert(name.to_string(), converter.shape(shape, &[], 0));
+            if is_required {
+                required.push(*name);
+            }
+        } else {
+            parameters.push(json!({
+                "name": name,
+                "in": "query",
+                "required": is_required,
+                "schema": converter.shape(shape, &[], 0),
+            }));
+        }
+    }
+
+    if !parameters.is_empty() {
+        operation.insert("parameters".to_owned(), json!(parameters));
+    }
+    if !properties.is_empty() {
+        operation.insert(
+            "requestBody".to_owned(),
+            json!({
+                "required": true,
+                "content": {
+                    "application/json": { "schema": object(properties, required) },
+                },
+            }),
+        );
+    }
+
+    let responses = match method.inner.output {
+        Some(ref shape) => json!({
+            "200": {
+                "description": "OK",
+                "content": {
+                    "application/json": { "schema": converter.shape(shape, &[], 0) },
+                },
+            },
+        }),
+        None => json!({ "204": { "description": "No Content" } }),
+    };
+    operation.insert("responses".to_owned(), responses);
+
+    if let Some(Literal::Object(auth)) = method.metadata.get("auth") {
+        if let Some(Literal::Array(scopes)) = auth.get("scopes") {
+            let scopes = scopes
+                .iter()
+                .filter_map(|scope| match scope {
+                    Literal::String(scope) => Some(unescape(scope)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            operation.insert("security".to_owned(), json!([{ SECURITY_SCHEME: scopes }]));
+        }
+    }
+
+    Value::Object(operation)
+}
+
+/// Returns the names of all `{placeholder}`s of a uri
+/// Verb and uri of the `rest` metadata of a method
+fn route<'a>(method: &Annotated<'a, ServiceMethod<'a>>) -> Option<(&'a str, &'a str)> {
+    let Some(Literal::Object(rest)) = method.metadata.get("rest") else {
+        return None;
+    };
+    match (rest.get("method"), rest.get("uri")) {
+        (Some(Literal::String(verb)), Some(Literal::String(uri))) => Some((verb, uri)),
+        _ => None,
+    }
+}
+
+/// Replaces the names of placeholders within a uri, e.g. `/orders/{}`
+fn path_template(uri: &str) -> String {
+    let mut output = String::new();
+    let mut rest = uri;
+    while let Some((before, after)) = rest.split_once('{') {
+        output.push_str(before);
+        output.push_str("{}");
+        rest = after.split_once('}').map_or("", |(_, after)| after);
+    }
+    output.push_str(rest);
+    output
+}
+
+fn find_placeholders(uri: &str) -> Vec<&str> {
+    uri.split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .collect()
+}
+
+fn metadata_string(metadata: &OrderedHashMap<&str, Literal>, key: &str) -> Option<String> {
+    match metadata.get(key) {
+        Some(Literal::String(value)) => Some(unescape(value)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let modules = [module(SCHEMA)];
+        let options = Map::new();
+        let json = emit_schema("todo", &modules, &options, Format::Json);
+        let yaml = emit_schema("todo", &modules, &options, Format::Yaml);
+        assert_golden("todo.openapi.json", &json);
+        assert_golden("todo.openapi.yaml", &yaml);
+    }
+
+    #[test]
+    fn reports_duplicate_routes() {
+        let source = "
+            service OrderService {
+              get(id: String) -> String { rest: { method: \"GET\", uri: \"/orders/{id}\" } },
+              find(orderId: String) -> String { rest: { method: \"get\", uri: \"/orders/{orderId}\" } },
+              update(id: String) { rest: { method: \"PUT\", uri: \"/orders/{id}\" } },
+              pending() -> String { rest: { method: \"GET\", uri: \"/orders/pending\" } },
+            }
+        ";
+        let messages = check(&[module(source)])
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            ["route GET /orders/{orderId} of `OrderService.find` is already used by `OrderService.get`"]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Runs an external generator, similar to `protoc` plugins. The plugin is
+//! started with the request below on stdin and has to answer on stdout.
+//!
+//! Request: `{ "version": 1, "schema": schema, "imports": { "./common.hgen": schema }, "options": {} }`,
+//! where every schema uses the JSON representation of the `json` emitter,
+//! `imports` holds the schemas imported by the entry schema, keyed by import
+//! path, and `options` are the target options of the project configuration.
+//!
+//! Response: `{ "files": [{ "path": "relative/file.kt", "content": "..." }], "error": null }`.
+//! Paths are relative to the output directory. A non-empty `error` aborts generation.
+
+use super::json;
+use crate::lang::loader::Module;
+use anyhow::{anyhow, bail, Context};
+use serde_json::{json, Map, Value};
+use std::{
+    io::Write,
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
+
+pub fn emit_schema(
+    plugin: &str,
+    name: &str,
+    modules: &[Module],
+    options: &Map<String, Value>,
+) -> anyhow::Result<Vec<(PathBuf, String)>> {
+    let module = &modules[0];
+
+    let imports = module
+        .schema
+        .imports
+        .iter()
+        .zip(&module.dependencies)
+        .map(|(import, dependency)| {
+            let dependency = &modules[*dependency];
+            let name = dependency.path.file_stem().unwrap().to_string_lossy();
+            (
+                import.path.to_owned(),
+                json::reflect_schema(&name, &dependency.schema),
+            )
+        })
+        .collect::<Map<_, _>>();
+
+    let request = json!({
+        "version": json::VERSION,
+        "schema": json::reflect_schema(name, &module.schema),
+        "imports": imports,
+        "options": options,
+    });
+
+    let mut child = Command::new(plugin)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("failed to start plugin {}", plugin))?;
+
+    // the request is written from another thread, since a plugin may start
+    // answering before reading all of it, blocking once both pipes are full
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = thread::spawn(move || stdin.write_all(request.to_string().as_bytes()));
+
+    let output = child.wait_with_output()?;
+    let written = writer.join().unwrap();
+
+    // a plugin which fails early closes stdin, so its exit status explains
+    // more than the broken pipe of the request
+    if !output.status.success() {
+        bail!("plugin {} exited with {}", plugin, output.status);
+    }
+    written.with_context(|| format!("failed to write request to plugin {}", plugin))?;
+
+    let response: Value = serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("plugin {} returned an invalid response", plugin))?;
+
+    match &response["error"] {
+        Value::Null => {}
+        Value::String(error) if error.is_empty() => {}
+        Value::String(error) => bail!("plugin {} failed: {}", plugin, error),
+        error => bail!("plugin {} failed: {}", plugin, error),
+    }
+
+    response["files"]
+        .as_array()
+        .ok_or_else(|| anyhow!("plugin {} returned no files", plugin))?
+        .iter()
+        .map(|file| {
+            let (Some(path), Some(content)) = (file["path"].as_str(), file["content"].as_str())
+            else {
+                bail!("plugin {} returned a file without path or content", plugin);
+            };
+
+            let path = Path::new(path);
+            if !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+            {
+                bail!(
+                    "plugin {} returned a path outside of the output directory: {}",
+                    plugin,
+                    path.display()
+                );
+            }
+
+            Ok((path.to_path_buf(), content.to_owned()))
+        })
+        .collect()
+}
@@ -0,0 +1,679 @@
+//! Emits a schema as a proto3 file, for gRPC consumers of the same models.
+//!
+//! Protocol Buffers has no aliases or generics, so aliases are replaced by
+//! their shape wherever they are used, and generic structs are emitted once
+//! per set of type arguments, e.g. `Page<Todo>` as `message PageTodo`. Lists
+//! and maps which cannot be nested directly are wrapped in synthesized
+//! messages holding a single `value` field.
+//!
+//! Field numbers follow the order of declaration. As they are part of the wire
+//! format, fields can be pinned to a number through their metadata, which the
+//! remaining fields skip over:
+//!
+//! ```text
+//! struct Todo {
+//!   id: UUID & { field: 1 },
+//!   title: String & { field: 3 },
+//! }
+//! ```
+//!
+//! Union variants and method inputs are pinned through the `fields` metadata
+//! of their union or method instead, e.g. `& { fields: { Circle: 2 } }`.
+//! Pinned numbers are validated by [`check`] before emitting, as they mean
+//! nothing to the other targets.
+
+use super::{resolve, substitute, type_name, ToPascalCase, ToSnakeCase};
+use crate::lang::{
+    diagnostic::{Diagnostic, Span},
+    loader::Module,
+    map::OrderedHashMap,
+    schema::*,
+};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, ops::RangeInclusive};
+
+/// Metadata key pinning the wire number of a field, e.g. `{ field: 1 }`
+const FIELD_NUMBER: &str = "field";
+
+/// Metadata key of unions and methods pinning the wire numbers of their
+/// variants and inputs, e.g. `{ fields: { id: 1 } }`
+const FIELD_NUMBERS: &str = "fields";
+
+/// Largest field number supported by Protocol Buffers
+const MAX_FIELD_NUMBER: i64 = (1 << 29) - 1;
+
+/// Field numbers reserved for the implementation of Protocol Buffers
+const RESERVED_FIELD_NUMBERS: RangeInclusive<i64> = 19000..=19999;
+
+/// Emits the first module, resolving aliases across all modules. All files
+/// are expected to share the package given by the `package` option.
+pub fn emit_schema(modules: &[Module], options: &Map<String, Value>) -> String {
+    let schema = &modules[0].schema;
+
+    let mut models = OrderedHashMap::new();
+    for module in modules.iter().rev() {
+        for (name, model) in module.schema.models.iter() {
+            models.insert(*name, model);
+        }
+    }
+
+    let mut emitter = Emitter {
+        models,
+        synthesized: Vec::new(),
+        instantiated: Vec::new(),
+        uses_wrappers: false,
+        uses_empty: false,
+    };
+
+    // emit models
+    let mut body = schema
+        .models
+        .iter()
+        .filter_map(|(name, def)| emitter.emit_model(name, def))
+        .collect::<Vec<_>>();
+
+    // emit services along with their request and response messages
+    for (name, service) in schema.services.iter() {
+        body.push(emitter.emit_service(name, service));
+    }
+
+    let mut output = String::new();
+
+    // emit header
+    output.push_str("// AUTOGENERATED FILE - DO NOT EDIT\n");
+    output.push('\n');
+    output.push_str("syntax = \"proto3\";\n");
+    output.push('\n');
+
+    if let Some(package) = options.get("package").and_then(Value::as_str) {
+        output.push_str(&format!("package {};\n", package));
+        output.push('\n');
+    }
+
+    // emit imports
+    let mut imports = schema
+        .imports
+        .iter()
+        .map(|import| {
+            let module_path = import.module_path();
+            format!("{}.proto", module_path.strip_prefix("./").unwrap_or(module_path))
+        })
+        .collect::<Vec<_>>();
+    if emitter.uses_empty {
+        imports.push("google/protobuf/empty.proto".to_owned());
+    }
+    if emitter.uses_wrappers {
+        imports.push("google/protobuf/wrappers.proto".to_owned());
+    }
+    if !imports.is_empty() {
+        for import in imports {
+            output.push_str(&format!("import \"{}\";\n", import));
+        }
+        output.push('\n');
+    }
+
+    body.extend(emitter.synthesized);
+    output.push_str(&body.join("\n"));
+
+    output
+}
+
+/// Validates the field numbers pinned through metadata, which only matter to
+/// Protocol Buffers, so they are checked before emitting instead of along with
+/// the schema
+pub fn check(modules: &[Module]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for module in modules {
+        let mut checker = Checker {
+            source: module.source,
+            diagnostics: Vec::new(),
+        };
+        checker.check_schema(&module.schema);
+        diagnostics.extend(
+            checker
+                .diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.with_file(module.path)),
+        );
+    }
+
+    diagnostics
+}
+
+struct Checker<'a> {
+    source: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    fn error(&mut self, name: &str, message: String) {
+        self.diagnostics
+            .push(Diagnostic::error(Span::of(self.source, name), message));
+    }
+
+    fn check_schema(&mut self, schema: &Schema<'a>) {
+        for (name, model) in schema.models.iter() {
+            match model {
+                Model::Struct(inner) => self.check_field_numbers(&inner.fields),
+                Model::Union(inner) => {
+                    for (_, variant) in inner.variants.iter() {
+                        if let Variant::Struct(fields) = variant {
+                            self.check_field_numbers(fields);
+                        }
+                    }
+                    let variants = inner.variants.iter().map(|(name, _)| *name);
+                    self.check_pinned_numbers(name, "variant", variants, &inner.metadata);
+                }
+                _ => {}
+            }
+        }
+
+        for (_, service) in schema.services.iter() {
+            for (name, method) in service.methods.iter() {
+                let inputs = method.inner.inputs.iter().map(|(name, _)| *name);
+                self.check_pinned_numbers(name, "input", inputs, &method.metadata);
+            }
+        }
+    }
+
+    /// Checks the numbers fields are pinned to through their metadata
+    fn check_field_numbers(&mut self, fields: &OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>) {
+        let numbers = fields
+            .iter()
+            .filter_map(|(name, field)| Some((*name, field.metadata.get(FIELD_NUMBER)?)))
+            .collect::<Vec<_>>();
+        self.check_numbers(numbers);
+    }
+
+    /// Checks the numbers variants or inputs are pinned to through the
+    /// metadata of their union or method, which has to name them
+    fn check_pinned_numbers(
+        &mut self,
+        owner: &'a str,
+        kind: &str,
+        names: impl Iterator<Item = &'a str>,
+        metadata: &OrderedHashMap<&'a str, Literal<'a>>,
+    ) {
+        let numbers = match metadata.get(FIELD_NUMBERS) {
+            None => return,
+            Some(Literal::Object(numbers)) => numbers,
+            Some(_) => {
+                self.error(
+                    owner,
+                    format!("`{}` has to map {} names to numbers", FIELD_NUMBERS, kind),
+                );
+                return;
+            }
+        };
+
+        let names = names.collect::<Vec<_>>();
+        for (name, _) in numbers.iter() {
+            if !names.contains(name) {
+                self.error(name, format!("unknown {} `{}`", kind, name));
+            }
+        }
+
+        self.check_numbers(
+            numbers
+                .iter()
+                .map(|(name, number)| (*name, number))
+                .collect(),
+        );
+    }
+
+    /// Reports numbers which are not integers, out of range, reserved or used
+    /// twice within the same message
+    fn check_numbers(&mut self, numbers: Vec<(&'a str, &Literal<'a>)>) {
+        let mut seen = HashMap::new();
+        for (name, number) in numbers {
+            let Literal::Int(number) = number else {
+                self.error(
+                    name,
+                    format!("field number of `{}` has to be an integer", name),
+                );
+                continue;
+            };
+
+            if *number <= 0 || *number > MAX_FIELD_NUMBER {
+                self.error(
+                    name,
+                    format!(
+                        "field number {} of `{}` is out of range, it has to be between 1 and {}",
+                        number, name, MAX_FIELD_NUMBER
+                    ),
+                );
+            } else if RESERVED_FIELD_NUMBERS.contains(number) {
+                self.error(
+                    name,
+                    format!(
+                        "field number {} of `{}` is reserved, as are {} to {}",
+                        number,
+                        name,
+                        RESERVED_FIELD_NUMBERS.start(),
+                        RESERVED_FIELD_NUMBERS.end()
+                    ),
+                );
+            } else if let Some(other) = seen.insert(*number, name) {
+                self.error(
+                    name,
+                    format!(
+                        "field number {} of `{}` is already used by `{}`",
+                        number, name, other
+                    ),
+                );
+            }
+        }
+    }
+}
+
+struct Emitter<'a> {
+    models: OrderedHashMap<&'a str, &'a Model<'a>>,
+    /// Messages for generic structs and nested collections, emitted after the
+    /// models and services of the schema
+    synthesized: Vec<String>,
+    /// Names of all synthesized messages, so that each is emitted only once
+    instantiated: Vec<String>,
+    uses_wrappers: bool,
+    uses_empty: bool,
+}
+
+impl<'a> Emitter<'a> {
+    /// Emits structs, enums and unions, while aliases, externals and generic
+    /// structs are emitted wherever they are used
+    fn emit_model(&mut self, name: &str, def: &'a Model<'a>) -> Option<String> {
+        match def {
+            Model::Struct(inner) if inner.type_parameters.is_empty() => {
+                Some(self.emit_message(name, &inner.fields, &[]))
+            }
+            Model::Enum(inner) => Some(emit_enum(name, inner)),
+            Model::Union(inner) => Some(self.emit_union(name, inner)),
+            _ => None,
+        }
+    }
+
+    fn emit_message(
+        &mut self,
+        name: &str,
+        fields: &OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>,
+        bindings: &[(&'a str, Shape<'a>)],
+    ) -> String {
+        let fields = self.emit_fields(fields, bindings, "  ");
+        emit_message(name, &fields)
+    }
+
+    fn emit_fields(
+        &mut self,
+        fields: &OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>,
+        bindings: &[(&'a str, Shape<'a>)],
+        indent: &str,
+    ) -> String {
+        let pins = fields
+            .iter()
+            .map(|(_, field)| match field.metadata.get(FIELD_NUMBER) {
+                Some(Literal::Int(number)) => Some(*number),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut output = String::new();
+        for ((name, field), number) in fields.iter().zip(field_numbers(&pins)) {
+            let shape = substitute(&field.inner, bindings);
+            output.push_str(&self.emit_field(name, &shape, number, indent));
+        }
+
+        output
+    }
+
+    fn emit_field(&mut self, name: &str, shape: &Shape<'a>, number: i64, indent: &str) -> String {
+        let field_name = name.to_snake_case();
+        let json_name = match to_json_name(&field_name) == name {
+            true => String::new(),
+            false => format!(" [json_name = \"{}\"]", name),
+        };
+
+        format!(
+            "{}{} {} = {}{};\n",
+            indent,
+            self.emit_field_type(shape),
+            field_name,
+            number,
+            json_name
+        )
+    }
+
+    /// Emits the type of a field, along with its label
+    fn emit_field_type(&mut self, shape: &Shape<'a>) -> String {
+        match self.resolve(shape) {
+            Shape::List(inner) => format!("repeated {}", self.emit_element_type(&inner)),
+            Shape::Map(key, value) => format!(
+                "map<{}, {}>",
+                self.emit_key_type(&key),
+                self.emit_element_type(&value)
+            ),
+            // lists and maps cannot be optional, so null is sent as empty
+            Shape::Nullable(inner) => match self.resolve(&inner) {
+                Shape::List(_) | Shape::Map(..) => self.emit_field_type(&inner),
+                inner => format!("optional {}", self.emit_element_type(&inner)),
+            },
+            shape => self.emit_element_type(&shape),
+        }
+    }
+
+    /// Emits the type of list items, map values and oneof fields, which have
+    /// no label
+    fn emit_element_type(&mut self, shape: &Shape<'a>) -> String {
+        match self.resolve(shape) {
+            Shape::Bool => "bool".to_owned(),
+            Shape::Int8 | Shape::Int16 | Shape::Int32 => "int32".to_owned(),
+            Shape::Int64 => "int64".to_owned(),
+            Shape::Float32 => "float".to_owned(),
+            Shape::Float64 => "double".to_owned(),
+            Shape::String => "string".to_owned(),
+            shape @ (Shape::List(_) | Shape::Map(..)) => {
+                let name = type_name(&shape);
+                if !self.instantiated.contains(&name) {
+                    self.instantiated.push(name.clone());
+                    let field = self.emit_field("value", &shape, 1, "  ");
+                    self.synthesized.push(emit_message(&name, &field));
+                }
+                name
+            }
+            Shape::Nullable(inner) => match self.resolve(&inner) {
+                inner @ (Shape::Bool
+                | Shape::Int8
+                | Shape::Int16
+                | Shape::Int32
+                | Shape::Int64
+                | Shape::Float32
+                | Shape::Float64
+                | Shape::String) => {
+                    self.uses_wrappers = true;
+                    format!("google.protobuf.{}", wrapper_name(&inner))
+                }
+                // messages and enums cannot hold null within collections
+                inner => self.emit_element_type(&inner),
+            },
+            Shape::Reference(name, arguments) if arguments.is_empty() => name.to_owned(),
+            Shape::Reference(name, arguments) => self.instantiate(name, &arguments),
+        }
+    }
+
+    /// Map keys are limited to integers and strings, so other keys are sent
+    /// as strings
+    fn emit_key_type(&mut self, shape: &Shape<'a>) -> String {
+        match self.resolve(shape) {
+            Shape::Bool => "bool".to_owned(),
+            Shape::Int8 | Shape::Int16 | Shape::Int32 => "int32".to_owned(),
+            Shape::Int64 => "int64".to_owned(),
+            _ => "string".to_owned(),
+        }
+    }
+
+    /// Emits a message for a generic struct with the given type arguments,
+    /// returning its name
+    fn instantiate(&mut self, name: &'a str, arguments: &[Annotated<'a, Shape<'a>>]) -> String {
+        let arguments = arguments
+            .iter()
+            .map(|argument| self.resolve(argument))
+            .collect::<Vec<_>>();
+        let message_name = type_name(&Shape::Reference(
+            name,
+            arguments.iter().cloned().map(Annotated::new).collect(),
+        ));
+
+        if !self.instantiated.contains(&message_name) {
+            self.instantiated.push(message_name.clone());
+            if let Some(Model::Struct(inner)) = self.models.get(name).copied() {
+                let bindings = inner
+                    .type_parameters
+                    .iter()
+                    .copied()
+                    .zip(arguments)
+                    .collect::<Vec<_>>();
+                let message = self.emit_message(&message_name, &inner.fields, &bindings);
+                self.synthesized.push(message);
+            }
+        }
+
+        message_name
+    }
+
+    fn resolve(&self, shape: &Shape<'a>) -> Shape<'a> {
+        resolve(&self.models, shape)
+    }
+
+    fn emit_union(&mut self, name: &str, def: &'a Union<'a>) -> String {
+        let mut output = String::new();
+        let mut variants = String::new();
+
+        output.push_str(&format!("message {} {{\n", name));
+
+        let pins = def
+            .variants
+            .iter()
+            .map(|(variant_name, _)| pinned_number(&def.metadata, variant_name))
+            .collect::<Vec<_>>();
+
+        for ((variant_name, variant), number) in def.variants.iter().zip(field_numbers(&pins)) {
+            let field_type = match variant {
+                Variant::Unit => {
+                    self.uses_empty = true;
+                    "google.protobuf.Empty".to_owned()
+                }
+                Variant::Tuple(shape) => self.emit_element_type(shape),
+                Variant::Struct(fields) => {
+                    output.push_str(&format!("  message {} {{\n", variant_name));
+                    output.push_str(&self.emit_fields(fields, &[], "    "));
+                    output.push_str("  }\n");
+                    output.push('\n');
+                    variant_name.to_string()
+                }
+            };
+
+            variants.push_str(&format!(
+                "    {} {} = {};\n",
+                field_type,
+                variant_name.to_snake_case(),
+                number
+            ));
+        }
+
+        output.push_str(&format!("  oneof {} {{\n", def.tag().to_snake_case()));
+        output.push_str(&variants);
+        output.push_str("  }\n");
+        output.push_str("}\n");
+
+        output
+    }
+
+    fn emit_service(&mut self, name: &str, service: &'a Service<'a>) -> String {
+        let mut messages = Vec::new();
+        let mut output = String::new();
+
+        output.push_str(&format!("service {} {{\n", name));
+
+        for (method_name, method) in service.methods.iter() {
+            let prefix = format!("{}{}", name, method_name.to_pascal_case());
+
+            let inputs = &method.inner.inputs;
+            let pins = inputs
+                .iter()
+                .map(|(input_name, _)| pinned_number(&method.metadata, input_name))
+                .collect::<Vec<_>>();
+
+            let mut fields = String::new();
+            for ((input_name, shape), number) in inputs.iter().zip(field_numbers(&pins)) {
+                fields.push_str(&self.emit_field(input_name, shape, number, "  "));
+            }
+            messages.push(emit_message(&format!("{}Request", prefix), &fields));
+
+            let response = match &method.inner.output {
+                None => {
+                    self.uses_empty = true;
+                    "google.protobuf.Empty".to_owned()
+                }
+                Some(shape) => match self.resolve(shape) {
+                    Shape::Reference(name, arguments) if self.is_message(name) => {
+                        self.emit_element_type(&Shape::Reference(name, arguments))
+                    }
+                    // anything but a message is wrapped in a response message
+                    _ => {
+                        let field = self.emit_field("value", shape, 1, "  ");
+                        messages.push(emit_message(&format!("{}Response", prefix), &field));
+                        format!("{}Response", prefix)
+                    }
+                },
+            };
+
+            output.push_str(&format!(
+                "  rpc {}({}Request) returns ({});\n",
+                method_name.to_pascal_case(),
+                prefix,
+                response
+            ));
+        }
+
+        output.push_str("}\n");
+
+        messages.push(output);
+        messages.join("\n")
+    }
+
+    fn is_message(&self, name: &str) -> bool {
+        matches!(
+            self.models.get(name),
+            Some(Model::Struct(_)) | Some(Model::Union(_))
+        )
+    }
+}
+
+/// Numbers fields in the order of declaration, skipping over the numbers
+/// other fields are pinned to
+fn field_numbers(pins: &[Option<i64>]) -> Vec<i64> {
+    let mut next = 1;
+    pins.iter()
+        .map(|pin| match pin {
+            Some(number) => *number,
+            None => {
+                while pins.contains(&Some(next)) {
+                    next += 1;
+                }
+                next += 1;
+                next - 1
+            }
+        })
+        .collect()
+}
+
+/// Number a variant or input is pinned to through the metadata of its union
+/// or method
+fn pinned_number(metadata: &OrderedHashMap<&str, Literal>, name: &str) -> Option<i64> {
+    match metadata.get(FIELD_NUMBERS) {
+        Some(Literal::Object(numbers)) => match numbers.get(name) {
+            Some(Literal::Int(number)) => Some(*number),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn emit_message(name: &str, fields: &str) -> String {
+    match fields.is_empty() {
+        true => format!("message {} {{}}\n", name),
+        false => format!("message {} {{\n{}}}\n", name, fields),
+    }
+}
+
+/// Emits an enum whose values are prefixed by its name, as enum values share
+/// the scope of the package. The zero value stands for an unset enum.
+fn emit_enum(name: &str, def: &Enum) -> String {
+    let mut output = String::new();
+    let prefix = name.to_snake_case().to_uppercase();
+
+    output.push_str(&format!("enum {} {{\n", name));
+    output.push_str(&format!("  {}_UNSPECIFIED = 0;\n", prefix));
+    for (number, field) in def.fields.iter().enumerate() {
+        output.push_str(&format!(
+            "  {}_{} = {};\n",
+            prefix,
+            field.to_snake_case().to_uppercase(),
+            number + 1
+        ));
+    }
+    output.push_str("}\n");
+
+    output
+}
+
+/// Name of the well-known wrapper type of a primitive
+fn wrapper_name(shape: &Shape) -> &'static str {
+    match shape {
+        Shape::Bool => "BoolValue",
+        Shape::Int64 => "Int64Value",
+        Shape::Float32 => "FloatValue",
+        Shape::Float64 => "DoubleValue",
+        Shape::String => "StringValue",
+        _ => "Int32Value",
+    }
+}
+
+/// Name of a field in the JSON mapping of protobuf, which protoc derives by
+/// camel casing the field name
+fn to_json_name(field_name: &str) -> String {
+    let mut output = String::new();
+    let mut capitalize = false;
+    for c in field_name.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            output.extend(c.to_uppercase());
+            capitalize = false;
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let module = module(SCHEMA);
+        assert_golden("todo.proto", &emit_schema(&[module], &Map::new()));
+    }
+
+    #[test]
+    fn reports_invalid_field_numbers() {
+        let source = "
+            struct Point {
+              x: Int32 & { field: 1 },
+              y: Int32 & { field: 1 },
+              z: Int32 & { field: 19000 },
+              w: Int32 & { field: 0 },
+            }
+
+            union Shape {
+              Circle(Float64),
+              Empty,
+            } & { fields: { Square: 1 } }
+        ";
+        let messages = check(&[module(source)])
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "field number 1 of `y` is already used by `x`",
+                "field number 19000 of `z` is reserved, as are 19000 to 19999",
+                "field number 0 of `w` is out of range, it has to be between 1 and 536870911",
+                "unknown variant `Square`",
+            ]
+        );
+    }
+}
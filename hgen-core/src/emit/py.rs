@@ -0,0 +1,641 @@
+use super::{rpc, ToSnakeCase};
+use crate::lang::schema::*;
+use serde_json::{Map, Value};
+
+/// Library backing the emitted models, chosen through the `models` option
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Backend {
+    Dataclasses,
+    Pydantic,
+}
+
+/// Emits models as dataclasses by default, or as pydantic models when the
+/// `models` option is `"pydantic"`. Services import the runtime from the
+/// `runtime` path, e.g. `./rpc`.
+pub fn emit_schema(
+    module_name: &str,
+    schema: &Schema,
+    runtime: &str,
+    options: &Map<String, Value>,
+) -> String {
+    let backend = match options.get("models").and_then(Value::as_str) {
+        Some("pydantic") => Backend::Pydantic,
+        _ => Backend::Dataclasses,
+    };
+
+    let mut output = String::new();
+
+    // emit header
+    output.push_str("# AUTOGENERATED FILE - DO NOT EDIT\n");
+    output.push('\n');
+    output.push_str("from __future__ import annotations\n");
+    output.push('\n');
+    match backend {
+        Backend::Dataclasses => output.push_str(DATACLASSES_IMPORTS),
+        Backend::Pydantic => output.push_str(PYDANTIC_IMPORTS),
+    }
+
+    // emit imports, along with the runtime of services and the externals
+    // implemented next to this module
+    let mut imports = schema.imports.iter().map(emit_import).collect::<Vec<_>>();
+    if !schema.services.is_empty() {
+        imports.push(emit_import(&Import {
+            path: runtime,
+            names: vec!["RpcError", "RpcRequest", "RpcResponse"],
+            is_wildcard: false,
+        }));
+    }
+    let externals = schema
+        .models
+        .iter()
+        .filter(|(_, def)| matches!(def, Model::External(_)))
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    if !externals.is_empty() {
+        imports.push(format!(
+            "from .{}_external import {}\n",
+            module_name,
+            externals.join(", ")
+        ));
+    }
+    if !imports.is_empty() {
+        output.push('\n');
+        output.push_str(&imports.join(""));
+    }
+    output.push('\n');
+
+    // top-level definitions are separated by two blank lines
+    let mut blocks = Vec::new();
+
+    // emit type parameters
+    let mut type_parameters = Vec::new();
+    for (_, def) in schema.models.iter() {
+        for type_parameter in def.type_parameters() {
+            if !type_parameters.contains(type_parameter) {
+                type_parameters.push(*type_parameter);
+            }
+        }
+    }
+    if !type_parameters.is_empty() {
+        blocks.push(
+            type_parameters
+                .iter()
+                .map(|name| format!("{} = TypeVar(\"{}\")\n", name, name))
+                .collect::<Vec<_>>()
+                .join(""),
+        );
+    }
+
+    if backend == Backend::Pydantic {
+        blocks.push(PYDANTIC_MODEL.to_owned());
+    }
+
+    // emit models
+    blocks.extend(
+        schema
+            .models
+            .iter()
+            .filter_map(|(name, def)| emit_model(name, def, backend)),
+    );
+
+    // emit aliases, which are evaluated right away and therefore come after
+    // the models and aliases they refer to
+    let aliases = sort_aliases(schema);
+    if !aliases.is_empty() {
+        blocks.push(
+            aliases
+                .iter()
+                .map(|(name, def)| format!("{} = {}\n", name, emit_shape(&def.shape.inner)))
+                .collect::<Vec<_>>()
+                .join(""),
+        );
+    }
+
+    // emit serialization helpers
+    blocks.push(
+        match backend {
+            Backend::Dataclasses => DATACLASSES_JSON,
+            Backend::Pydantic => PYDANTIC_JSON,
+        }
+        .to_owned(),
+    );
+
+    // emit consumers
+    blocks.extend(
+        schema
+            .services
+            .iter()
+            .map(|(name, service)| emit_consumer(name, service)),
+    );
+
+    output.push('\n');
+    output.push_str(&blocks.join("\n\n"));
+
+    output
+}
+
+const DATACLASSES_IMPORTS: &str = r#"import dataclasses
+import enum
+import json
+import typing
+from typing import Any, Callable, Dict, Generic, List, Literal, Optional, TypeVar, Union
+"#;
+
+const PYDANTIC_IMPORTS: &str = r#"import enum
+import functools
+import json
+from typing import Annotated, Any, Callable, Dict, Generic, List, Literal, Optional, TypeVar, Union
+
+import pydantic_core
+from pydantic import BaseModel, ConfigDict, Field, TypeAdapter
+"#;
+
+/// Base class of all pydantic models, accepting fields by their Python name
+/// as well as by their name on the wire
+const PYDANTIC_MODEL: &str = r#"class _Model(BaseModel):
+    model_config = ConfigDict(populate_by_name=True)
+"#;
+
+/// Converts models from and to JSON, using the wire names kept in the metadata
+/// of dataclass fields
+const DATACLASSES_JSON: &str = r#"def encode(value: Any) -> Any:
+    """Converts a value into its JSON representation."""
+    if isinstance(value, enum.Enum):
+        return value.value
+    if dataclasses.is_dataclass(value):
+        return {
+            field.metadata.get("name", field.name): encode(getattr(value, field.name))
+            for field in dataclasses.fields(value)
+        }
+    if isinstance(value, (list, tuple)):
+        return [encode(item) for item in value]
+    if isinstance(value, dict):
+        return {_encode_key(key): encode(item) for key, item in value.items()}
+    return value
+
+
+def decode(data: Any, type_: Any) -> Any:
+    """Converts the JSON representation of a value of the given type into the value."""
+    origin = typing.get_origin(type_)
+    arguments = typing.get_args(type_)
+    if origin is Union:
+        if data is None and type(None) in arguments:
+            return None
+        variants = [argument for argument in arguments if argument is not type(None)]
+        if len(variants) == 1:
+            return decode(data, variants[0])
+        for variant in variants:
+            tag, name = _variant_tag(variant)
+            if data.get(tag) == name:
+                return decode(data, variant)
+        raise ValueError(f"unknown variant {data!r}")
+    if origin is list:
+        return [decode(item, arguments[0]) for item in data]
+    if origin is dict:
+        return {_decode_key(key, arguments[0]): decode(item, arguments[1]) for key, item in data.items()}
+    if origin is Literal or isinstance(type_, TypeVar):
+        return data
+    model = origin or type_
+    if isinstance(model, type) and issubclass(model, enum.Enum):
+        return model(data)
+    if dataclasses.is_dataclass(model):
+        hints = typing.get_type_hints(model)
+        bindings = dict(zip(getattr(model, "__parameters__", ()), arguments))
+        return model(**{
+            field.name: decode(data.get(field.metadata.get("name", field.name)), _bind(hints[field.name], bindings))
+            for field in dataclasses.fields(model)
+        })
+    if model is float and data is not None:
+        return float(data)
+    return data
+
+
+def to_json(value: Any) -> str:
+    return json.dumps(encode(value))
+
+
+def from_json(text: Union[str, bytes], type_: Any) -> Any:
+    return decode(json.loads(text), type_)
+
+
+def _encode_key(key: Any) -> str:
+    key = encode(key)
+    if isinstance(key, bool):
+        return "true" if key else "false"
+    return str(key)
+
+
+def _decode_key(key: str, type_: Any) -> Any:
+    if type_ is bool:
+        return key == "true"
+    if type_ is int:
+        return int(key)
+    return decode(key, type_)
+
+
+def _variant_tag(variant: Any) -> Any:
+    hints = typing.get_type_hints(variant)
+    for field in dataclasses.fields(variant):
+        if typing.get_origin(hints[field.name]) is Literal:
+            return field.metadata.get("name", field.name), field.default
+    raise TypeError(f"{variant!r} is not a variant")
+
+
+def _bind(type_: Any, bindings: Dict[Any, Any]) -> Any:
+    if isinstance(type_, TypeVar):
+        return bindings.get(type_, type_)
+    parameters = getattr(type_, "__parameters__", ())
+    if parameters and bindings:
+        return type_[tuple(bindings.get(parameter, parameter) for parameter in parameters)]
+    return type_
+"#;
+
+/// Converts models from and to JSON through pydantic, which validates them
+const PYDANTIC_JSON: &str = r#"def encode(value: Any) -> Any:
+    """Converts a value into its JSON representation."""
+    return pydantic_core.to_jsonable_python(value, by_alias=True)
+
+
+def decode(data: Any, type_: Any) -> Any:
+    """Converts the JSON representation of a value of the given type into the value."""
+    return _adapter(type_).validate_python(data)
+
+
+def to_json(value: Any) -> str:
+    return json.dumps(encode(value))
+
+
+def from_json(text: Union[str, bytes], type_: Any) -> Any:
+    return _adapter(type_).validate_json(text)
+
+
+@functools.lru_cache(maxsize=None)
+def _adapter(type_: Any) -> TypeAdapter:
+    return TypeAdapter(type_)
+"#;
+
+/// Emits the runtime shared by the services of all modules
+pub fn emit_runtime() -> String {
+    let mut output = String::new();
+
+    output.push_str("# AUTOGENERATED FILE - DO NOT EDIT\n");
+    output.push('\n');
+    output.push_str("from typing import Any, Dict\n");
+    output.push_str("\n\n");
+    output.push_str(RPC);
+
+    output
+}
+
+/// Requests and responses are plain dictionaries, so only errors get a class
+const RPC: &str = r#"RpcRequest = Dict[str, Any]
+RpcResponse = Dict[str, Any]
+
+
+class RpcError(Exception):
+    def __init__(self, code: str, message: str) -> None:
+        super().__init__(message)
+        self.code = code
+"#;
+
+fn emit_import(import: &Import) -> String {
+    let module_path = import.module_path();
+
+    // `./common` becomes `.common` and `../common` becomes `..common`
+    let mut module = String::from(".");
+    let mut path = module_path.strip_prefix("./").unwrap_or(module_path);
+    while let Some(rest) = path.strip_prefix("../") {
+        module.push('.');
+        path = rest;
+    }
+    module.push_str(&path.replace('/', "."));
+
+    match import.is_wildcard {
+        true => format!("from {} import *\n", module),
+        false => format!("from {} import {}\n", module, import.names.join(", ")),
+    }
+}
+
+/// Emits structs, enums and unions, while aliases are emitted once all models
+/// are defined and externals are imported
+fn emit_model(name: &str, def: &Model, backend: Backend) -> Option<String> {
+    match def {
+        Model::Struct(inner) => Some(emit_struct(
+            name,
+            &inner.type_parameters,
+            inner
+                .fields
+                .iter()
+                .map(|(name, shape)| emit_field(name, &shape.inner, backend))
+                .collect(),
+            backend,
+        )),
+        Model::Enum(inner) => Some(emit_enum(name, inner)),
+        Model::Union(inner) => Some(emit_union(name, inner, backend)),
+        Model::Alias(_) | Model::External(_) => None,
+    }
+}
+
+fn emit_struct(
+    name: &str,
+    type_parameters: &[&str],
+    fields: Vec<String>,
+    backend: Backend,
+) -> String {
+    let mut output = String::new();
+
+    let mut bases = Vec::new();
+    match backend {
+        Backend::Dataclasses => output.push_str("@dataclasses.dataclass\n"),
+        Backend::Pydantic => bases.push("_Model".to_owned()),
+    }
+    if !type_parameters.is_empty() {
+        bases.push(format!("Generic[{}]", type_parameters.join(", ")));
+    }
+
+    match bases.is_empty() {
+        true => output.push_str(&format!("class {}:\n", name)),
+        false => output.push_str(&format!("class {}({}):\n", name, bases.join(", "))),
+    }
+    if fields.is_empty() {
+        output.push_str("    pass\n");
+    }
+    for field in fields {
+        output.push_str(&field);
+    }
+
+    output
+}
+
+/// Emits a field named in snake case, keeping its name on the wire as an
+/// alias or in the metadata of the field. Nullable fields are optional for
+/// pydantic, while dataclasses require them to keep the order of fields.
+fn emit_field(name: &str, shape: &Shape, backend: Backend) -> String {
+    let field_name = escape(&name.to_snake_case());
+    let is_renamed = field_name != name;
+    let is_nullable = matches!(shape, Shape::Nullable(_));
+
+    let value = match backend {
+        Backend::Dataclasses if is_renamed => {
+            format!(" = dataclasses.field(metadata={{\"name\": \"{}\"}})", name)
+        }
+        Backend::Pydantic if is_renamed && is_nullable => {
+            format!(" = Field(default=None, alias=\"{}\")", name)
+        }
+        Backend::Pydantic if is_renamed => format!(" = Field(alias=\"{}\")", name),
+        Backend::Pydantic if is_nullable => " = None".to_owned(),
+        _ => String::new(),
+    };
+
+    format!("    {}: {}{}\n", field_name, emit_shape(shape), value)
+}
+
+/// Emits the tag of a union variant, which always holds the variant name
+fn emit_tag(tag: &str, variant_name: &str, backend: Backend) -> String {
+    let field_name = escape(&tag.to_snake_case());
+    let literal = format!("Literal[\"{}\"]", variant_name);
+
+    match (backend, field_name == tag) {
+        (_, true) => format!("    {}: {} = \"{}\"\n", field_name, literal, variant_name),
+        (Backend::Dataclasses, false) => format!(
+            "    {}: {} = dataclasses.field(default=\"{}\", metadata={{\"name\": \"{}\"}})\n",
+            field_name, literal, variant_name, tag
+        ),
+        (Backend::Pydantic, false) => format!(
+            "    {}: {} = Field(default=\"{}\", alias=\"{}\")\n",
+            field_name, literal, variant_name, tag
+        ),
+    }
+}
+
+fn emit_enum(name: &str, def: &Enum) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("class {}(enum.Enum):\n", name));
+    if def.fields.is_empty() {
+        output.push_str("    pass\n");
+    }
+    for field in def.fields.iter() {
+        output.push_str(&format!("    {} = \"{}\"\n", escape(field), field));
+    }
+
+    output
+}
+
+/// Emits a class per variant mirroring its wire format, with the tag and the
+/// payload as fields, along with the union of all variant classes
+fn emit_union(name: &str, def: &Union, backend: Backend) -> String {
+    let mut classes = Vec::new();
+    let mut variants = Vec::new();
+
+    for (variant_name, variant) in def.variants.iter() {
+        let class_name = format!("{}{}", name, variant_name);
+
+        let mut fields = Vec::new();
+        match variant {
+            Variant::Unit => {}
+            Variant::Tuple(shape) => fields.push(emit_field(def.content(), shape, backend)),
+            Variant::Struct(payload) => {
+                let payload_name = format!("{}Payload", class_name);
+                classes.push(emit_struct(
+                    &payload_name,
+                    &[],
+                    payload
+                        .iter()
+                        .map(|(name, shape)| emit_field(name, &shape.inner, backend))
+                        .collect(),
+                    backend,
+                ));
+                fields.push(emit_field(
+                    def.content(),
+                    &Shape::Reference(&payload_name, vec![]),
+                    backend,
+                ));
+            }
+        }
+        // the tag has a default and therefore comes last
+        fields.push(emit_tag(def.tag(), variant_name, backend));
+
+        classes.push(emit_struct(&class_name, &[], fields, backend));
+        variants.push(class_name);
+    }
+
+    let union = match (variants.len(), backend) {
+        (0, _) => "typing.NoReturn".to_owned(),
+        (1, _) => variants[0].clone(),
+        (_, Backend::Dataclasses) => format!("Union[{}]", variants.join(", ")),
+        (_, Backend::Pydantic) => format!(
+            "Annotated[Union[{}], Field(discriminator=\"{}\")]",
+            variants.join(", "),
+            escape(&def.tag().to_snake_case())
+        ),
+    };
+
+    let mut output = classes.join("\n\n");
+    if !output.is_empty() {
+        output.push_str("\n\n");
+    }
+    output.push_str(&format!("{} = {}\n", name, union));
+
+    output
+}
+
+/// Orders aliases such that every alias comes after the aliases it refers to
+fn sort_aliases<'a>(schema: &'a Schema) -> Vec<(&'a str, &'a Alias<'a>)> {
+    let mut pending = schema
+        .models
+        .iter()
+        .filter_map(|(name, def)| match def {
+            Model::Alias(inner) => Some((*name, inner)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut sorted = Vec::new();
+
+    while !pending.is_empty() {
+        let position = pending
+            .iter()
+            .position(|(_, def)| {
+                !pending
+                    .iter()
+                    .any(|(name, _)| refers_to(&def.shape.inner, name))
+            })
+            // aliases referring to each other cannot be ordered, so keep them as declared
+            .unwrap_or(0);
+        sorted.push(pending.remove(position));
+    }
+
+    sorted
+}
+
+fn refers_to(shape: &Shape, name: &str) -> bool {
+    match shape {
+        Shape::List(inner) => refers_to(inner, name),
+        Shape::Nullable(inner) => refers_to(inner, name),
+        Shape::Map(key, value) => refers_to(key, name) || refers_to(value, name),
+        Shape::Reference(reference, arguments) => {
+            *reference == name || arguments.iter().any(|argument| refers_to(argument, name))
+        }
+        _ => false,
+    }
+}
+
+fn emit_consumer(name: &str, service: &Service) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("class {}Consumer:\n", name));
+    output.push_str(
+        "    def __init__(self, send: Callable[[RpcRequest], RpcResponse]) -> None:\n",
+    );
+    output.push_str("        self.send = send\n");
+    output.push_str("        self._id = 0\n");
+    output.push('\n');
+    output.push_str("    def _request(self, method: str, params: Dict[str, Any]) -> Any:\n");
+    output.push_str("        self._id += 1\n");
+    output.push_str(&format!(
+        "        response = self.send({{\"version\": {}, \"id\": self._id, \"service\": \"{}\", \"method\": method, \"params\": params}})\n",
+        rpc::VERSION,
+        name
+    ));
+    output.push_str("        if \"error\" in response:\n");
+    output.push_str(
+        "            raise RpcError(response[\"error\"][\"code\"], response[\"error\"][\"message\"])\n",
+    );
+    output.push_str("        return response.get(\"result\")\n");
+
+    for (method_name, method) in service.methods.iter() {
+        output.push('\n');
+        output.push_str(&emit_consumer_method(method_name, method));
+    }
+
+    output
+}
+
+fn emit_consumer_method(name: &str, method: &Annotated<ServiceMethod>) -> String {
+    let mut output = String::new();
+
+    let mut parameters = vec!["self".to_owned()];
+    let mut params = Vec::new();
+    for (input_name, shape) in method.inner.inputs.iter() {
+        let parameter_name = escape(&input_name.to_snake_case());
+        parameters.push(format!("{}: {}", parameter_name, emit_shape(shape)));
+        params.push(format!("\"{}\": encode({})", input_name, parameter_name));
+    }
+
+    output.push_str(&format!(
+        "    def {}({}) -> {}:\n",
+        escape(&name.to_snake_case()),
+        parameters.join(", "),
+        match method.inner.output {
+            Some(ref shape) => emit_shape(shape),
+            None => "None".to_owned(),
+        }
+    ));
+
+    let request = format!("self._request(\"{}\", {{{}}})", name, params.join(", "));
+    match method.inner.output {
+        Some(ref shape) => output.push_str(&format!(
+            "        return decode({}, {})\n",
+            request,
+            emit_shape(shape)
+        )),
+        None => output.push_str(&format!("        {}\n", request)),
+    }
+
+    output
+}
+
+fn emit_shape(shape: &Shape) -> String {
+    match shape {
+        Shape::Bool => "bool".to_owned(),
+        Shape::Int8 | Shape::Int16 | Shape::Int32 | Shape::Int64 => "int".to_owned(),
+        Shape::Float32 | Shape::Float64 => "float".to_owned(),
+        Shape::String => "str".to_owned(),
+        Shape::List(inner) => format!("List[{}]", emit_shape(inner)),
+        Shape::Map(key, value) => format!("Dict[{}, {}]", emit_shape(key), emit_shape(value)),
+        Shape::Reference(name, arguments) if arguments.is_empty() => name.to_string(),
+        Shape::Reference(name, arguments) => format!(
+            "{}[{}]",
+            name,
+            arguments
+                .iter()
+                .map(|argument| emit_shape(argument))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Shape::Nullable(inner) => format!("Optional[{}]", emit_shape(inner)),
+    }
+}
+
+/// Appends an underscore to names which are reserved in Python
+fn escape(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield",
+    ];
+
+    match KEYWORDS.contains(&name) {
+        true => format!("{}_", name),
+        false => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let module = module(SCHEMA);
+        let output = emit_schema("todo", &module.schema, &rpc::path(0), &Map::new());
+        assert_golden("todo.py", &output);
+    }
+
+    #[test]
+    fn emits_golden_runtime() {
+        assert_golden("rpc.py", &emit_runtime());
+    }
+}
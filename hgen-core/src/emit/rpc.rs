@@ -0,0 +1,50 @@
+//! The envelope of requests and responses, which is the same for all targets
+//! so clients and providers of different languages can talk to each other:
+//!
+//! ```text
+//! { "version": 1, "id": 1, "service": "TodoService", "method": "getById", "params": { "id": 42 } }
+//! { "version": 1, "id": 1, "result": { ... } }
+//! { "version": 1, "id": 1, "error": { "code": "unknown_method", "message": "..." } }
+//! ```
+//!
+//! Runtime code of the targets refers to the values below through
+//! placeholders like `{VERSION}`, which [`expand`] fills in. The runtime is
+//! emitted once per target, as a module named [`MODULE`] next to the entry
+//! module, and imported by every module with services.
+
+/// Name of the runtime module, without extension
+pub const MODULE: &str = "rpc";
+
+/// Version of the envelope, increased whenever its layout changes
+pub const VERSION: u32 = 1;
+
+/// The version of a request is not supported by the provider
+pub const UNSUPPORTED_VERSION: &str = "unsupported_version";
+/// The request was sent to a provider of another service
+pub const UNKNOWN_SERVICE: &str = "unknown_service";
+/// The service has no method of the requested name
+pub const UNKNOWN_METHOD: &str = "unknown_method";
+/// The params do not match the inputs of the method
+pub const INVALID_PARAMS: &str = "invalid_params";
+/// The provider failed to handle the request
+pub const INTERNAL: &str = "internal";
+
+/// Fills in the placeholders of runtime code
+pub fn expand(template: &str) -> String {
+    template
+        .replace("{VERSION}", &VERSION.to_string())
+        .replace("{UNSUPPORTED_VERSION}", UNSUPPORTED_VERSION)
+        .replace("{UNKNOWN_SERVICE}", UNKNOWN_SERVICE)
+        .replace("{UNKNOWN_METHOD}", UNKNOWN_METHOD)
+        .replace("{INVALID_PARAMS}", INVALID_PARAMS)
+        .replace("{INTERNAL}", INTERNAL)
+}
+
+/// Path of the runtime module, as imported by a module `depth` directories
+/// below it, e.g. `../rpc` for a depth of one
+pub fn path(depth: usize) -> String {
+    match depth {
+        0 => format!("./{}", MODULE),
+        _ => format!("{}{}", "../".repeat(depth), MODULE),
+    }
+}
@@ -0,0 +1,559 @@
+use super::{rpc, ToPascalCase, ToSnakeCase};
+use crate::lang::schema::*;
+
+/// Emits the models and services of a schema, with services importing the
+/// runtime from the `runtime` path, e.g. `./rpc`
+pub fn emit_schema(_name: &str, schema: &Schema, runtime: &str) -> String {
+    let mut output = String::new();
+
+    output.push_str("use serde::{Serialize, Deserialize};\n");
+    output.push_str(
+        &schema
+            .imports
+            .iter()
+            .map(emit_import)
+            .collect::<Vec<_>>()
+            .join(""),
+    );
+    if !schema.services.is_empty() {
+        output.push_str(&emit_import(&Import {
+            path: runtime,
+            names: Vec::new(),
+            is_wildcard: true,
+        }));
+    }
+
+    output.push_str(
+        &schema
+            .models
+            .iter()
+            .map(|(name, def)| emit_model(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+
+    if !schema.services.is_empty() {
+        output.push('\n');
+        output.push_str(
+            &schema
+                .services
+                .iter()
+                .map(|(name, def)| emit_service(name, def))
+                .collect::<Vec<_>>()
+                .join(""),
+        );
+    }
+
+    output
+}
+
+/// Emits the runtime shared by the services of all modules, which depends on
+/// serde_json, so schemas without services can do without it
+pub fn emit_runtime() -> String {
+    let mut output = String::new();
+
+    output.push_str("use serde::{Serialize, Deserialize};\n");
+    output.push('\n');
+    output.push_str(&rpc::expand(RPC));
+
+    output
+}
+
+fn emit_import(import: &Import) -> String {
+    let module_path = import
+        .module_path()
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .map(|segment| if segment == ".." { "super" } else { segment })
+        .collect::<Vec<_>>()
+        .join("::");
+
+    if import.is_wildcard {
+        format!("use super::{}::*;\n", module_path)
+    } else {
+        format!(
+            "use super::{}::{{{}}};\n",
+            module_path,
+            import.names.join(", ")
+        )
+    }
+}
+
+fn emit_model(name: &str, def: &Model) -> String {
+    match def {
+        Model::Struct(inner) => emit_struct(name, inner),
+        Model::Enum(inner) => emit_enum(name, inner),
+        Model::Union(inner) => emit_union(name, inner),
+        Model::Alias(inner) => format!(
+            "pub type {}{} = {};\n",
+            name,
+            emit_type_parameters(&inner.type_parameters),
+            emit_shape(&inner.shape.inner)
+        ),
+        Model::External(_) => format!("pub use external::{};\n", name),
+    }
+}
+
+fn emit_struct(name: &str, def: &Struct) -> String {
+    let mut output = String::new();
+
+    output.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+    output.push_str(&format!(
+        "pub struct {}{} ",
+        name,
+        emit_type_parameters(&def.type_parameters)
+    ));
+    output.push_str("{\n");
+    def.fields.iter().for_each(|(name, shape)| {
+        output.push_str(&emit_field(name, &shape.inner, "    ", true));
+    });
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_enum(name: &str, def: &Enum) -> String {
+    let mut output = String::new();
+
+    output.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+    output.push_str(&format!("pub enum {} ", name));
+    output.push_str("{\n");
+    def.fields.iter().for_each(|value| {
+        output.push_str(&format!("    {},\n", value));
+    });
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_union(name: &str, def: &Union) -> String {
+    let mut output = String::new();
+
+    output.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+    output.push_str(&format!(
+        "#[serde(tag = \"{}\", content = \"{}\")]\n",
+        def.tag(),
+        def.content()
+    ));
+    output.push_str(&format!("pub enum {} ", name));
+    output.push_str("{\n");
+    def.variants.iter().for_each(|(name, variant)| match variant {
+        Variant::Unit => output.push_str(&format!("    {},\n", name)),
+        Variant::Tuple(shape) => output.push_str(&format!("    {}({}),\n", name, emit_shape(shape))),
+        Variant::Struct(fields) => {
+            output.push_str(&format!("    {} {{\n", name));
+            fields.iter().for_each(|(name, shape)| {
+                output.push_str(&emit_field(name, &shape.inner, "        ", false));
+            });
+            output.push_str("    },\n");
+        }
+    });
+    output.push_str("}\n");
+
+    output
+}
+
+/// Envelope types, along with the transport used by clients
+const RPC: &str = r#"/// Version of the envelope of requests and responses
+pub const RPC_VERSION: u32 = {VERSION};
+
+/// Request to call a method of a service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRequest {
+    pub version: u32,
+    /// Identifies the response to this request, either a number or a string
+    pub id: serde_json::Value,
+    pub service: String,
+    pub method: String,
+    /// Inputs of the method, keyed by their names in the schema
+    pub params: serde_json::Value,
+}
+
+/// Response to a request, carrying the same id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub version: u32,
+    pub id: serde_json::Value,
+    #[serde(flatten)]
+    pub outcome: RpcOutcome,
+}
+
+impl RpcResponse {
+    pub fn new(id: serde_json::Value, outcome: Result<serde_json::Value, RpcError>) -> Self {
+        Self {
+            version: RPC_VERSION,
+            id,
+            outcome: match outcome {
+                Ok(result) => RpcOutcome::Result(result),
+                Err(error) => RpcOutcome::Error(error),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcOutcome {
+    /// Serialized output of the method, `null` for methods without one
+    Result(serde_json::Value),
+    Error(RpcError),
+}
+
+/// Error of a request, with a `code` of `{UNSUPPORTED_VERSION}`, `{UNKNOWN_SERVICE}`,
+/// `{UNKNOWN_METHOD}`, `{INVALID_PARAMS}` or `{INTERNAL}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: String,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_owned(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Sends requests of clients to the providers of services, e.g. over HTTP
+pub trait Transport {
+    type Error: From<serde_json::Error> + From<RpcError>;
+
+    fn send(
+        &self,
+        request: RpcRequest,
+    ) -> impl std::future::Future<Output = Result<RpcResponse, Self::Error>> + Send;
+}
+
+/// Reasons a provider could not handle a request
+#[derive(Debug)]
+pub enum DispatchError<E> {
+    /// The service has no method of the given name
+    UnknownMethod(String),
+    /// The params do not match the inputs of the method
+    InvalidParams(serde_json::Error),
+    /// The output of the method could not be serialized
+    InvalidOutput(serde_json::Error),
+    /// The provider returned an error
+    Provider(E),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for DispatchError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownMethod(method) => write!(f, "unknown method `{}`", method),
+            Self::InvalidParams(error) => write!(f, "invalid params: {}", error),
+            Self::InvalidOutput(error) => write!(f, "invalid output: {}", error),
+            Self::Provider(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for DispatchError<E> {}
+
+impl<E: std::fmt::Display> From<DispatchError<E>> for RpcError {
+    fn from(error: DispatchError<E>) -> Self {
+        let code = match error {
+            DispatchError::UnknownMethod(_) => "{UNKNOWN_METHOD}",
+            DispatchError::InvalidParams(_) => "{INVALID_PARAMS}",
+            DispatchError::InvalidOutput(_) | DispatchError::Provider(_) => "{INTERNAL}",
+        };
+        RpcError::new(code, error.to_string())
+    }
+}
+"#;
+
+fn emit_service(name: &str, service: &Service) -> String {
+    let mut output = String::new();
+
+    // emit inputs and outputs
+    service.methods.iter().for_each(|(method_name, method)| {
+        let prefix = format!("{}{}", name, method_name.to_pascal_case());
+
+        output.push('\n');
+        output.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+        output.push_str(&format!("pub struct {}Input ", prefix));
+        output.push_str("{\n");
+        method.inner.inputs.iter().for_each(|(name, shape)| {
+            output.push_str(&emit_field(name, shape, "    ", true));
+        });
+        output.push_str("}\n");
+        output.push('\n');
+        output.push_str(&format!(
+            "pub type {}Output = {};\n",
+            prefix,
+            emit_output(&method.inner)
+        ));
+    });
+
+    // emit provider
+    output.push('\n');
+    output.push_str(&format!("pub trait {}Provider ", name));
+    output.push_str("{\n");
+    output.push_str("    type Error;\n");
+    service.methods.iter().for_each(|(method_name, method)| {
+        output.push('\n');
+        output.push_str(&format!(
+            "    fn {}(&self{}) -> impl std::future::Future<Output = Result<{}, Self::Error>> + Send;\n",
+            method_name.to_snake_case(),
+            emit_parameters(&method.inner),
+            emit_output(&method.inner)
+        ));
+    });
+    output.push_str("}\n");
+
+    // emit dispatcher
+    output.push('\n');
+    output.push_str(&format!(
+        "/// Decodes the params of a request to `{}`, calls the matching method of\n",
+        name
+    ));
+    output.push_str("/// the provider and encodes its output\n");
+    output.push_str(&format!(
+        "pub async fn dispatch_{}<P: {}Provider>(\n",
+        name.to_snake_case(),
+        name
+    ));
+    output.push_str("    provider: &P,\n");
+    output.push_str("    method: &str,\n");
+    output.push_str("    params: serde_json::Value,\n");
+    output.push_str(") -> Result<serde_json::Value, DispatchError<P::Error>> {\n");
+    output.push_str("    match method {\n");
+    service.methods.iter().for_each(|(method_name, method)| {
+        output.push_str(&format!("        \"{}\" => {{\n", method_name));
+        output.push_str(&format!(
+            "            let {}: {}{}Input = serde_json::from_value(params).map_err(DispatchError::InvalidParams)?;\n",
+            if method.inner.inputs.is_empty() { "_" } else { "inputs" },
+            name,
+            method_name.to_pascal_case()
+        ));
+        output.push_str(&format!(
+            "            let output = provider.{}({}).await.map_err(DispatchError::Provider)?;\n",
+            method_name.to_snake_case(),
+            method
+                .inner
+                .inputs
+                .iter()
+                .map(|(name, _)| format!("inputs.{}", name.to_snake_case()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        output.push_str("            serde_json::to_value(output).map_err(DispatchError::InvalidOutput)\n");
+        output.push_str("        }\n");
+    });
+    output.push_str("        _ => Err(DispatchError::UnknownMethod(method.to_owned())),\n");
+    output.push_str("    }\n");
+    output.push_str("}\n");
+
+    // emit handler
+    output.push('\n');
+    output.push_str(&format!(
+        "/// Answers a request to `{}`, turning errors into error responses\n",
+        name
+    ));
+    output.push_str(&format!(
+        "pub async fn handle_{}<P: {}Provider>(provider: &P, request: RpcRequest) -> RpcResponse\n",
+        name.to_snake_case(),
+        name
+    ));
+    output.push_str("where\n");
+    output.push_str("    P::Error: std::fmt::Display,\n");
+    output.push_str("{\n");
+    output.push_str("    let outcome = if request.version != RPC_VERSION {\n");
+    output.push_str("        let message = format!(\"unsupported version {}\", request.version);\n");
+    output.push_str(&format!(
+        "        Err(RpcError::new(\"{}\", message))\n",
+        rpc::UNSUPPORTED_VERSION
+    ));
+    output.push_str(&format!("    }} else if request.service != \"{}\" {{\n", name));
+    output.push_str("        let message = format!(\"unknown service `{}`\", request.service);\n");
+    output.push_str(&format!(
+        "        Err(RpcError::new(\"{}\", message))\n",
+        rpc::UNKNOWN_SERVICE
+    ));
+    output.push_str("    } else {\n");
+    output.push_str(&format!(
+        "        dispatch_{}(provider, &request.method, request.params)\n",
+        name.to_snake_case()
+    ));
+    output.push_str("            .await\n");
+    output.push_str("            .map_err(RpcError::from)\n");
+    output.push_str("    };\n");
+    output.push('\n');
+    output.push_str("    RpcResponse::new(request.id, outcome)\n");
+    output.push_str("}\n");
+
+    // emit client
+    output.push('\n');
+    output.push_str(&format!("pub struct {}Client<T> ", name));
+    output.push_str("{\n");
+    output.push_str("    pub transport: T,\n");
+    output.push_str("    next_id: std::sync::atomic::AtomicU64,\n");
+    output.push_str("}\n");
+    output.push('\n');
+    output.push_str(&format!("impl<T: Transport> {}Client<T> ", name));
+    output.push_str("{\n");
+    output.push_str("    pub fn new(transport: T) -> Self {\n");
+    output.push_str("        Self {\n");
+    output.push_str("            transport,\n");
+    output.push_str("            next_id: Default::default(),\n");
+    output.push_str("        }\n");
+    output.push_str("    }\n");
+    output.push('\n');
+    output.push_str("    async fn request<O: serde::de::DeserializeOwned>(\n");
+    output.push_str("        &self,\n");
+    output.push_str("        method: &str,\n");
+    output.push_str("        params: impl Serialize,\n");
+    output.push_str("    ) -> Result<O, T::Error> {\n");
+    output.push_str("        let id = self.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);\n");
+    output.push_str("        let request = RpcRequest {\n");
+    output.push_str("            version: RPC_VERSION,\n");
+    output.push_str("            id: id.into(),\n");
+    output.push_str(&format!("            service: \"{}\".to_owned(),\n", name));
+    output.push_str("            method: method.to_owned(),\n");
+    output.push_str("            params: serde_json::to_value(params)?,\n");
+    output.push_str("        };\n");
+    output.push('\n');
+    output.push_str("        match self.transport.send(request).await?.outcome {\n");
+    output.push_str("            RpcOutcome::Result(result) => Ok(serde_json::from_value(result)?),\n");
+    output.push_str("            RpcOutcome::Error(error) => Err(error.into()),\n");
+    output.push_str("        }\n");
+    output.push_str("    }\n");
+    service.methods.iter().for_each(|(method_name, method)| {
+        output.push('\n');
+        output.push_str(&format!(
+            "    pub async fn {}(&self{}) -> Result<{}, T::Error> {{\n",
+            method_name.to_snake_case(),
+            emit_parameters(&method.inner),
+            emit_output(&method.inner)
+        ));
+        let fields = method
+            .inner
+            .inputs
+            .iter()
+            .map(|(name, _)| name.to_snake_case())
+            .collect::<Vec<_>>();
+        output.push_str(&format!(
+            "        let inputs = {}{}Input {};\n",
+            name,
+            method_name.to_pascal_case(),
+            match fields.is_empty() {
+                true => "{}".to_owned(),
+                false => format!("{{ {} }}", fields.join(", ")),
+            }
+        ));
+        output.push_str(&format!(
+            "        self.request(\"{}\", inputs).await\n",
+            method_name
+        ));
+        output.push_str("    }\n");
+    });
+    output.push_str("}\n");
+
+    output
+}
+
+/// Emits a field, keeping its name from the schema on the wire if it is not
+/// snake case already
+fn emit_field(name: &str, shape: &Shape, indent: &str, is_public: bool) -> String {
+    let mut output = String::new();
+
+    if name.to_snake_case() != name {
+        output.push_str(&format!("{}#[serde(rename = \"{}\")]\n", indent, name));
+    }
+    output.push_str(&format!(
+        "{}{}{}: {},\n",
+        indent,
+        if is_public { "pub " } else { "" },
+        name.to_snake_case(),
+        emit_shape(shape)
+    ));
+
+    output
+}
+
+fn emit_parameters(method: &ServiceMethod) -> String {
+    method
+        .inputs
+        .iter()
+        .map(|(name, shape)| format!(", {}: {}", name.to_snake_case(), emit_shape(shape)))
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn emit_output(method: &ServiceMethod) -> String {
+    match method.output {
+        Some(ref shape) => emit_shape(shape),
+        None => "()".to_owned(),
+    }
+}
+
+fn emit_shape(def: &Shape) -> String {
+    match def {
+        Shape::Bool => "bool".to_owned(),
+        Shape::Int8 => "i8".to_owned(),
+        Shape::Int16 => "i16".to_owned(),
+        Shape::Int32 => "i32".to_owned(),
+        Shape::Int64 => "i64".to_owned(),
+        Shape::Float32 => "f32".to_owned(),
+        Shape::Float64 => "f64".to_owned(),
+        Shape::String => "String".to_owned(),
+        Shape::Nullable(inner) => format!("Option<{}>", emit_shape(inner)),
+        Shape::List(inner) => format!("Vec<{}>", emit_shape(inner)),
+        Shape::Map(key, value) => format!(
+            "std::collections::HashMap<{}, {}>",
+            emit_shape(key),
+            emit_shape(value)
+        ),
+        Shape::Reference(name, arguments) => format!("{}{}", name, emit_arguments(arguments)),
+    }
+}
+
+fn emit_arguments(arguments: &[Annotated<Shape>]) -> String {
+    if arguments.is_empty() {
+        return "".to_owned();
+    }
+
+    format!(
+        "<{}>",
+        arguments
+            .iter()
+            .map(|argument| emit_shape(argument))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn emit_type_parameters(type_parameters: &[&str]) -> String {
+    if type_parameters.is_empty() {
+        return "".to_owned();
+    }
+
+    format!("<{}>", type_parameters.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let module = module(SCHEMA);
+        assert_golden("todo.rs", &emit_schema("todo", &module.schema, &rpc::path(0)));
+    }
+
+    #[test]
+    fn emits_golden_runtime() {
+        assert_golden("rpc.rs", &emit_runtime());
+    }
+}
@@ -0,0 +1,714 @@
+use super::rpc;
+use crate::lang::{map::OrderedHashMap, schema::*};
+
+/// Emits the models and services of a schema, with services importing the
+/// runtime from the `runtime` path, e.g. `./rpc`
+pub fn emit_schema(file_name: &str, schema: &Schema, runtime: &str) -> String {
+    let mut output = String::new();
+
+    // emit header
+    output.push_str("// AUTOGENERATED FILE - DO NOT EDIT\n");
+    output.push('\n');
+
+    // emit imports, along with the runtime of services
+    let mut imports = schema.imports.iter().map(emit_import).collect::<Vec<_>>();
+    if !schema.services.is_empty() {
+        imports.push(emit_import(&Import {
+            path: runtime,
+            names: RPC_NAMES.to_vec(),
+            is_wildcard: false,
+        }));
+    }
+    if !imports.is_empty() {
+        output.push_str(&imports.join(""));
+        output.push('\n');
+    }
+
+    // emit models
+    output.push_str(
+        &schema
+            .models
+            .iter()
+            .map(|(name, def)| emit_model(name, def, file_name))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+
+    // emit consumers
+    output.push_str(
+        &schema
+            .services
+            .iter()
+            .map(|(name, shape)| emit_consumer(name, shape))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+
+    // emit providers
+    output.push_str(
+        &schema
+            .services
+            .iter()
+            .map(|(name, def)| emit_provider(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+
+    // emit dispatchers
+    output.push_str(
+        &schema
+            .services
+            .iter()
+            .map(|(name, def)| emit_dispatcher(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push('\n');
+
+    // emit metadata
+    output.push_str("// prettier-ignore\n");
+    output.push_str(&format!(
+        "export const $schema = {}",
+        reflect_schema(schema)
+    ));
+
+    output
+}
+
+/// Emits the runtime shared by the services of all modules
+pub fn emit_runtime() -> String {
+    let mut output = String::new();
+
+    output.push_str("// AUTOGENERATED FILE - DO NOT EDIT\n");
+    output.push('\n');
+    output.push_str(&rpc::expand(RPC));
+
+    output
+}
+
+/// Names exported by the runtime
+const RPC_NAMES: [&str; 5] = [
+    "RpcError",
+    "RpcRequest",
+    "RpcResponse",
+    "requireParams",
+    "respond",
+];
+
+/// Envelope types, along with the runtime used by providers
+const RPC: &str = r#"export interface RpcRequest {
+  version: {VERSION};
+  id: number | string;
+  service: string;
+  method: string;
+  params: { [name: string]: any };
+}
+
+export type RpcResponse =
+  | { version: {VERSION}; id: number | string; result: any }
+  | { version: {VERSION}; id: number | string; error: { code: string; message: string } };
+
+export class RpcError extends Error {
+  constructor(public code: string, message: string) {
+    super(message);
+  }
+}
+
+export function requireParams(params: { [name: string]: any }, names: string[]) {
+  for (const name of names) {
+    if (params[name] === undefined || params[name] === null) {
+      throw new RpcError('{INVALID_PARAMS}', `missing param ${name}`);
+    }
+  }
+}
+
+export async function respond(
+  service: string,
+  request: RpcRequest,
+  dispatch: (method: string, params: any) => Promise<any>
+): Promise<RpcResponse> {
+  try {
+    if (request.version !== {VERSION}) {
+      throw new RpcError('{UNSUPPORTED_VERSION}', `unsupported version ${request.version}`);
+    }
+    if (request.service !== service) {
+      throw new RpcError('{UNKNOWN_SERVICE}', `unknown service ${request.service}`);
+    }
+    const params: unknown = request.params;
+    if (typeof params !== 'object' || params === null || Array.isArray(params)) {
+      throw new RpcError('{INVALID_PARAMS}', 'params have to be an object');
+    }
+    const result = await dispatch(request.method, params);
+    return { version: {VERSION}, id: request.id, result: result ?? null };
+  } catch (error) {
+    const code = error instanceof RpcError ? error.code : '{INTERNAL}';
+    const message = error instanceof Error ? error.message : String(error);
+    return { version: {VERSION}, id: request.id, error: { code, message } };
+  }
+}
+"#;
+
+fn emit_import(import: &Import) -> String {
+    let module_path = import.module_path();
+
+    format!(
+        "import {{ {} }} from '{}{}';\n",
+        import.names.join(", "),
+        if module_path.starts_with('.') { "" } else { "./" },
+        module_path
+    )
+}
+
+fn emit_provider(name: &str, service: &Service) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("export interface {}Provider ", name));
+    output.push_str("{\n");
+    output.push_str(
+        &service
+            .methods
+            .iter()
+            .map(|(name, def)| emit_provider_method(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_provider_method(name: &str, method: &Annotated<ServiceMethod>) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "  {}({}): Promise<{}>;\n",
+        name,
+        method
+            .inner
+            .inputs
+            .iter()
+            .map(|(name, shape)| format!("{}: {}", name, emit_shape(shape)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        match method.inner.output {
+            Some(ref shape) => emit_shape(shape),
+            None => "void".to_owned(),
+        }
+    ));
+
+    output
+}
+
+fn emit_dispatcher(name: &str, service: &Service) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "export function create{}Dispatcher(provider: {}Provider) ",
+        name, name
+    ));
+    output.push_str("{\n");
+    output.push_str("  const dispatch = (method: string, params: any): Promise<any> => {\n");
+    output.push_str("    switch (method) {\n");
+    service.methods.iter().for_each(|(name, def)| {
+        output.push_str(&format!("      case \"{}\":\n", name));
+        let required = def
+            .inner
+            .inputs
+            .iter()
+            .filter(|(_, shape)| !matches!(shape.inner, Shape::Nullable(_)))
+            .map(|(name, _)| format!("\"{}\"", name))
+            .collect::<Vec<_>>();
+        if !required.is_empty() {
+            output.push_str(&format!(
+                "        requireParams(params, [{}]);\n",
+                required.join(", ")
+            ));
+        }
+        output.push_str(&format!(
+            "        return provider.{}({});\n",
+            name,
+            def.inner
+                .inputs
+                .iter()
+                .map(|(name, _)| format!("params.{}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    });
+    output.push_str("      default:\n");
+    output.push_str(&format!(
+        "        throw new RpcError('{}', `unknown method ${{method}} of {}`);\n",
+        rpc::UNKNOWN_METHOD,
+        name
+    ));
+    output.push_str("    }\n");
+    output.push_str("  };\n");
+    output.push('\n');
+    output.push_str(&format!(
+        "  return (request: RpcRequest): Promise<RpcResponse> => respond(\"{}\", request, dispatch);\n",
+        name
+    ));
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_consumer(name: &str, service: &Service) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("export class {}Consumer ", name));
+    output.push_str("{\n");
+    output.push_str("  private id = 0;\n\n");
+    output.push_str(
+        "  constructor(\n    protected send: (request: RpcRequest) => Promise<RpcResponse>\n  ) {}\n\n",
+    );
+    output.push_str(
+        "  protected async request(method: string, params: RpcRequest['params']): Promise<any> {\n",
+    );
+    output.push_str(&format!(
+        "    const response = await this.send({{ version: {}, id: ++this.id, service: \"{}\", method, params }});\n",
+        rpc::VERSION,
+        name
+    ));
+    output.push_str("    if ('error' in response) {\n");
+    output.push_str("      throw new RpcError(response.error.code, response.error.message);\n");
+    output.push_str("    }\n");
+    output.push_str("    return response.result;\n");
+    output.push_str("  }\n\n");
+    output.push_str(
+        &service
+            .methods
+            .iter()
+            .map(|(name, def)| emit_consumer_method(name, def))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_consumer_method(name: &str, method: &Annotated<ServiceMethod>) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "  {}({}): Promise<{}> {{\n",
+        name,
+        method
+            .inner
+            .inputs
+            .iter()
+            .map(|(name, shape)| format!("{}: {}", name, emit_shape(shape)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        match method.inner.output {
+            Some(ref shape) => emit_shape(shape),
+            None => "void".to_owned(),
+        }
+    ));
+    output.push_str(&format!(
+        "    return this.request(\"{}\", {{ {} }});\n",
+        name,
+        method
+            .inner
+            .inputs
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+    output.push_str("  }\n");
+
+    output
+}
+
+fn reflect_schema(schema: &Schema) -> String {
+    let mut output = String::new();
+
+    output.push('{');
+
+    // emit models
+    output.push_str(&format!(
+        "models:{{{}}}",
+        schema
+            .models
+            .iter()
+            .map(|(name, def)| reflect_model(name, def))
+            .collect::<Vec<_>>()
+            .join(",")
+    ));
+
+    // emit services
+    output.push(',');
+    output.push_str(&format!(
+        "services:{{{}}}",
+        schema
+            .services
+            .iter()
+            .map(|(name, def)| reflect_service(name, def))
+            .collect::<Vec<_>>()
+            .join(",")
+    ));
+
+    output.push_str("} as const;");
+
+    output
+}
+
+fn emit_model(name: &str, def: &Model, file_name: &str) -> String {
+    match def {
+        Model::Struct(s) => emit_struct(name, s),
+        Model::Enum(e) => emit_enum(name, e),
+        Model::Union(u) => emit_union(name, u),
+        Model::Alias(a) => emit_alias(name, a),
+        Model::External(_) => format!(
+            "import {{ {} }} from './{}.external';\nexport {{ {} }};\n",
+            name, file_name, name
+        ),
+    }
+}
+
+fn reflect_model(name: &str, def: &Model) -> String {
+    match def {
+        Model::Struct(inner) => format!("{}:{{{}}}", name, reflect_struct(inner)),
+        Model::Enum(inner) => format!("{}:{{{}}}", name, reflect_enum(inner)),
+        Model::Union(inner) => format!("{}:{{{}}}", name, reflect_union(inner)),
+        Model::Alias(inner) => {
+            format!(
+                "{}:{{type:'alias',{}inner:{{{}}}}}",
+                name,
+                reflect_type_parameters(&inner.type_parameters),
+                reflect_annotated_shape(&inner.shape)
+            )
+        }
+        Model::External(inner) => {
+            format!(
+                "{}:{{type:'external',inner:{{{}}}}}",
+                name,
+                reflect_annotated_shape(&inner.shape)
+            )
+        }
+    }
+}
+
+fn reflect_service(name: &str, def: &Service) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("{}:{{", name));
+    output.push_str("type:'service',");
+
+    output.push_str(&format!(
+        "methods:{{{}}}",
+        def.methods
+            .iter()
+            .map(|(name, def)| reflect_method(name, def))
+            .collect::<Vec<_>>()
+            .join(",")
+    ));
+
+    output.push('}');
+
+    output
+}
+
+fn reflect_method(name: &str, def: &Annotated<ServiceMethod>) -> String {
+    format!(
+        "{}:{{inputs:{{{}}},output:{{{}}},metadata:{{{}}}}}",
+        name,
+        def.inner
+            .inputs
+            .iter()
+            .map(|(name, shape)| format!("{}:{{{}}}", name, reflect_shape(shape)))
+            .collect::<Vec<_>>()
+            .join(","),
+        match def.inner.output {
+            Some(ref shape) => reflect_shape(shape),
+            None => "".to_owned(),
+        },
+        reflect_metadata(&def.metadata),
+    )
+}
+
+fn reflect_type_parameters(type_parameters: &[&str]) -> String {
+    if type_parameters.is_empty() {
+        return "".to_owned();
+    }
+
+    format!(
+        "typeParameters:[{}],",
+        type_parameters
+            .iter()
+            .map(|name| format!("'{}'", name))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+fn reflect_struct(def: &Struct) -> String {
+    format!(
+        "type:'struct',{}fields:{{{}}}",
+        reflect_type_parameters(&def.type_parameters),
+        def.fields
+            .iter()
+            .map(|(name, shape)| format!("{}:{{{}}}", name, reflect_annotated_shape(shape)))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+fn reflect_enum(def: &Enum) -> String {
+    format!(
+        "type:'enum',fields:{{{}}}",
+        def.fields
+            .iter()
+            .map(|value| format!("{}:''", value))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+fn reflect_union(def: &Union) -> String {
+    format!(
+        "type:'union',tag:'{}',content:'{}',variants:{{{}}},metadata:{{{}}}",
+        def.tag(),
+        def.content(),
+        def.variants
+            .iter()
+            .map(|(name, variant)| format!("{}:{{{}}}", name, reflect_variant(variant)))
+            .collect::<Vec<_>>()
+            .join(","),
+        reflect_metadata(&def.metadata),
+    )
+}
+
+fn reflect_variant(variant: &Variant) -> String {
+    match variant {
+        Variant::Unit => "type:'unit'".to_owned(),
+        Variant::Tuple(shape) => format!("type:'tuple',inner:{{{}}}", reflect_shape(shape)),
+        Variant::Struct(fields) => format!(
+            "type:'struct',fields:{{{}}}",
+            fields
+                .iter()
+                .map(|(name, shape)| format!("{}:{{{}}}", name, reflect_annotated_shape(shape)))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+fn reflect_shape(shape: &Shape) -> String {
+    match shape {
+        Shape::Bool => "type:'bool'".to_owned(),
+        Shape::Int8 => "type:'int8'".to_owned(),
+        Shape::Int16 => "type:'int16'".to_owned(),
+        Shape::Int32 => "type:'int32'".to_owned(),
+        Shape::Int64 => "type:'int64'".to_owned(),
+        Shape::Float32 => "type:'float32'".to_owned(),
+        Shape::Float64 => "type:'float64'".to_owned(),
+        Shape::String => "type:'string'".to_owned(),
+        Shape::Nullable(inner) => {
+            format!("type:'nullable',inner:{{{}}}", reflect_shape(inner))
+        }
+        Shape::List(inner) => format!("type:'list',inner:{{{}}}", reflect_shape(inner)),
+        Shape::Map(key, value) => {
+            format!(
+                "type:'map',key:{{{}}},value:{{{}}}",
+                reflect_shape(key),
+                reflect_shape(value)
+            )
+        }
+        Shape::Reference(name, arguments) if arguments.is_empty() => {
+            format!("type:'reference',name:'{}'", name)
+        }
+        Shape::Reference(name, arguments) => format!(
+            "type:'reference',name:'{}',arguments:[{}]",
+            name,
+            arguments
+                .iter()
+                .map(|argument| format!("{{{}}}", reflect_shape(argument)))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+fn reflect_annotated_shape(shape: &Annotated<Shape>) -> String {
+    format!(
+        "{},metadata:{{{}}}",
+        reflect_shape(&shape.inner),
+        reflect_metadata(&shape.metadata),
+    )
+}
+
+fn reflect_metadata(metadata: &OrderedHashMap<&str, Literal>) -> String {
+    metadata
+        .iter()
+        .map(|(k, v)| format!("{}:{}", k, reflect_literal(v)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn reflect_literal(literal: &Literal) -> String {
+    match literal {
+        Literal::Bool(inner) => if *inner { "true" } else { "false" }.to_owned(),
+        Literal::String(inner) => format!("'{}'", inner),
+        Literal::Char(inner) => serde_json::Value::String(inner.to_string()).to_string(),
+        Literal::Int(inner) => inner.to_string(),
+        Literal::Float(inner) => inner.to_string(),
+        Literal::Array(inner) => format!(
+            "[{}]",
+            inner
+                .iter()
+                .map(reflect_literal)
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        Literal::Object(inner) => format!(
+            "{{{}}}",
+            inner
+                .iter()
+                .map(|(k, v)| format!("{}:{}", k, reflect_literal(v)))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+fn emit_alias(name: &str, alias: &Alias) -> String {
+    format!(
+        "export type {}{} = {};\n",
+        name,
+        emit_type_parameters(&alias.type_parameters),
+        emit_shape(&alias.shape.inner)
+    )
+}
+
+fn emit_enum(name: &str, message: &Enum) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("export enum {} ", name));
+    output.push_str("{\n");
+    message.fields.iter().for_each(|name| {
+        output.push_str(&format!("  {} = '{}',\n", name, name));
+    });
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_union(name: &str, def: &Union) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("export type {} =\n", name));
+    output.push_str(
+        &def.variants
+            .iter()
+            .map(|(variant_name, variant)| {
+                let tag = format!("{}: '{}'", def.tag(), variant_name);
+                match variant {
+                    Variant::Unit => format!("  | {{ {} }}", tag),
+                    Variant::Tuple(shape) => format!(
+                        "  | {{ {}; {}: {} }}",
+                        tag,
+                        def.content(),
+                        emit_shape(shape)
+                    ),
+                    Variant::Struct(fields) => format!(
+                        "  | {{ {}; {}: {{ {} }} }}",
+                        tag,
+                        def.content(),
+                        fields
+                            .iter()
+                            .map(|(name, shape)| format!("{}: {}", name, emit_shape(&shape.inner)))
+                            .collect::<Vec<_>>()
+                            .join("; ")
+                    ),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    if def.variants.is_empty() {
+        output.push_str("  never");
+    }
+    output.push_str(";\n");
+
+    output
+}
+
+fn emit_struct(name: &str, message: &Struct) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "export class {}{} ",
+        name,
+        emit_type_parameters(&message.type_parameters)
+    ));
+    output.push_str("{\n");
+    message.fields.iter().for_each(|(name, shape)| {
+        output.push_str(&format!("  {}: {};\n", name, emit_shape(&shape.inner)));
+    });
+    output.push_str("}\n");
+
+    output
+}
+
+fn emit_shape(shape: &Shape) -> String {
+    match shape {
+        Shape::Bool { .. } => "boolean".to_owned(),
+        Shape::Int8 { .. } => "number".to_owned(),
+        Shape::Int16 { .. } => "number".to_owned(),
+        Shape::Int32 { .. } => "number".to_owned(),
+        Shape::Int64 { .. } => "bigint".to_owned(),
+        Shape::Float32 { .. } => "number".to_owned(),
+        Shape::Float64 { .. } => "number".to_owned(),
+        Shape::String { .. } => "string".to_owned(),
+        Shape::Nullable(inner) => format!("({} | null)", emit_shape(inner)),
+        Shape::List(inner) => format!("({}[])", emit_shape(inner)),
+        Shape::Map(key, value) => format!("Map<{}, {}>", emit_shape(key), emit_shape(value)),
+        Shape::Reference(name, arguments) => format!("{}{}", name, emit_arguments(arguments)),
+    }
+}
+
+fn emit_arguments(arguments: &[Annotated<Shape>]) -> String {
+    if arguments.is_empty() {
+        return "".to_owned();
+    }
+
+    format!(
+        "<{}>",
+        arguments
+            .iter()
+            .map(|argument| emit_shape(argument))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn emit_type_parameters(type_parameters: &[&str]) -> String {
+    if type_parameters.is_empty() {
+        return "".to_owned();
+    }
+
+    format!("<{}>", type_parameters.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::tests::{assert_golden, module, SCHEMA};
+
+    #[test]
+    fn emits_golden_output() {
+        let module = module(SCHEMA);
+        assert_golden("todo.ts", &emit_schema("todo", &module.schema, &rpc::path(0)));
+    }
+
+    #[test]
+    fn emits_golden_runtime() {
+        assert_golden("rpc.ts", &emit_runtime());
+    }
+}
@@ -0,0 +1,88 @@
+//! Writes JSON values as block style YAML, for emitters offering both formats.
+//! Strings are quoted JSON style unless they are plain words, which YAML reads
+//! back the same way.
+
+use serde_json::Value;
+
+pub fn to_yaml(value: &Value) -> String {
+    let mut output = String::new();
+
+    if is_block(value) {
+        write_block(&mut output, value, 0);
+    } else {
+        output.push_str(&write_scalar(value));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Non-empty objects and arrays are written over multiple lines
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Object(entries) => !entries.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+fn write_block(output: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(entries) => {
+            for (key, value) in entries {
+                output.push_str(&" ".repeat(indent));
+                output.push_str(&write_string(key));
+                output.push(':');
+                if is_block(value) {
+                    output.push('\n');
+                    write_block(output, value, indent + 2);
+                } else {
+                    output.push(' ');
+                    output.push_str(&write_scalar(value));
+                    output.push('\n');
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                output.push_str(&" ".repeat(indent));
+                output.push_str("- ");
+                if is_block(item) {
+                    // the first line of the item goes right after the dash
+                    let mut block = String::new();
+                    write_block(&mut block, item, indent + 2);
+                    output.push_str(&block[indent + 2..]);
+                } else {
+                    output.push_str(&write_scalar(item));
+                    output.push('\n');
+                }
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn write_scalar(value: &Value) -> String {
+    match value {
+        Value::String(value) => write_string(value),
+        Value::Object(_) => "{}".to_owned(),
+        Value::Array(_) => "[]".to_owned(),
+        _ => value.to_string(),
+    }
+}
+
+fn write_string(value: &str) -> String {
+    let is_plain = value.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '/')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+        && !matches!(
+            value.to_ascii_lowercase().as_str(),
+            "true" | "false" | "null" | "yes" | "no" | "on" | "off" | "y" | "n"
+        );
+
+    match is_plain {
+        true => value.to_owned(),
+        false => Value::String(value.to_owned()).to_string(),
+    }
+}
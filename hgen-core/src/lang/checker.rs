@@ -0,0 +1,464 @@
+use super::{
+    diagnostic::{Diagnostic, Span},
+    loader::Module,
+    schema::*,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Models visible from within a module, pointing at the module declaring them
+type Scope<'m, 'a> = HashMap<&'a str, (usize, &'m Model<'a>)>;
+
+/// Validates parsed modules before any code is emitted. Reports unknown and
+/// duplicate names, recursive aliases and invalid map keys.
+pub fn check(modules: &[Module]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let scopes = modules
+        .iter()
+        .enumerate()
+        .map(|(index, module)| build_scope(modules, index, module, &mut diagnostics))
+        .collect::<Vec<_>>();
+
+    for (index, module) in modules.iter().enumerate() {
+        let mut checker = Checker {
+            modules,
+            scopes: &scopes,
+            index,
+            diagnostics: Vec::new(),
+        };
+        checker.check_module(module);
+        diagnostics.extend(
+            checker
+                .diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.with_file(module.path)),
+        );
+    }
+
+    diagnostics
+}
+
+fn build_scope<'m, 'a>(
+    modules: &'m [Module<'a>],
+    index: usize,
+    module: &'m Module<'a>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Scope<'m, 'a> {
+    let mut scope = Scope::new();
+
+    for (import, dependency) in module.schema.imports.iter().zip(&module.dependencies) {
+        for name in import.names.iter() {
+            match modules[*dependency].schema.models.get(name) {
+                Some(model) => {
+                    scope.insert(*name, (*dependency, model));
+                }
+                None => diagnostics.push(
+                    Diagnostic::error(
+                        Span::of(module.source, name),
+                        format!("`{}` is not declared in {}", name, import.path),
+                    )
+                    .with_file(module.path),
+                ),
+            }
+        }
+    }
+
+    for (name, model) in module.schema.models.iter() {
+        if let Some((dependency, _)) = scope.get(name) {
+            if *dependency != index {
+                diagnostics.push(
+                    Diagnostic::error(
+                        Span::of(module.source, name),
+                        format!(
+                            "`{}` is already imported from {}",
+                            name,
+                            modules[*dependency].path.display()
+                        ),
+                    )
+                    .with_file(module.path),
+                );
+                continue;
+            }
+        }
+
+        scope.insert(*name, (index, model));
+    }
+
+    scope
+}
+
+struct Checker<'s, 'm, 'a> {
+    modules: &'m [Module<'a>],
+    scopes: &'s [Scope<'m, 'a>],
+    index: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'s, 'm, 'a> Checker<'s, 'm, 'a> {
+    fn error(&mut self, name: &str, message: String) {
+        let source = self.modules[self.index].source;
+        self.diagnostics
+            .push(Diagnostic::error(Span::of(source, name), message));
+    }
+
+    /// Reports every name which has already been seen before
+    fn check_unique<'n>(&mut self, kind: &str, names: impl Iterator<Item = &'n str>) {
+        let mut seen = HashSet::new();
+        for name in names {
+            if !seen.insert(name) {
+                self.error(name, format!("duplicate {} `{}`", kind, name));
+            }
+        }
+    }
+
+    fn check_module(&mut self, module: &'m Module<'a>) {
+        let schema = &module.schema;
+
+        self.check_unique("model", schema.models.iter().map(|(name, _)| *name));
+        self.check_unique("service", schema.services.iter().map(|(name, _)| *name));
+
+        for (name, model) in schema.models.iter() {
+            self.check_model(name, model);
+        }
+
+        for (_, service) in schema.services.iter() {
+            self.check_unique("method", service.methods.iter().map(|(name, _)| *name));
+
+            for (name, method) in service.methods.iter() {
+                self.check_unique("input", method.inner.inputs.iter().map(|(name, _)| *name));
+
+                for (input, shape) in method.inner.inputs.iter() {
+                    self.check_shape(input, shape, &[]);
+                }
+                if let Some(ref shape) = method.inner.output {
+                    self.check_shape(name, shape, &[]);
+                }
+            }
+        }
+    }
+
+    fn check_model(&mut self, name: &'a str, model: &'m Model<'a>) {
+        let scope = model.type_parameters();
+        self.check_unique("type parameter", scope.iter().copied());
+
+        match model {
+            Model::Struct(inner) => {
+                self.check_unique("field", inner.fields.iter().map(|(name, _)| *name));
+                for (field, shape) in inner.fields.iter() {
+                    self.check_shape(field, &shape.inner, scope);
+                }
+            }
+            Model::Enum(inner) => {
+                self.check_unique("variant", inner.fields.iter().copied());
+            }
+            Model::Union(inner) => {
+                self.check_unique("variant", inner.variants.iter().map(|(name, _)| *name));
+                for (variant_name, variant) in inner.variants.iter() {
+                    match variant {
+                        Variant::Unit => {}
+                        Variant::Tuple(shape) => self.check_shape(variant_name, shape, scope),
+                        Variant::Struct(fields) => {
+                            self.check_unique("field", fields.iter().map(|(name, _)| *name));
+                            for (field, shape) in fields.iter() {
+                                self.check_shape(field, &shape.inner, scope);
+                            }
+                        }
+                    }
+                }
+            }
+            Model::Alias(inner) => {
+                self.check_shape(name, &inner.shape.inner, scope);
+                if let Some(cycle) = self.find_alias_cycle(self.index, name, &mut Vec::new()) {
+                    self.error(name, format!("recursive alias: {}", cycle.join(" -> ")));
+                }
+            }
+            Model::External(inner) => self.check_shape(name, &inner.shape.inner, scope),
+        }
+    }
+
+    /// Checks a shape used by `location`, which is the closest name to report errors at
+    fn check_shape(&mut self, location: &'a str, shape: &Shape<'a>, scope: &[&'a str]) {
+        match shape {
+            Shape::Nullable(inner) => self.check_shape(location, inner, scope),
+            Shape::List(inner) => self.check_shape(location, inner, scope),
+            Shape::Map(key, value) => {
+                if !self.is_valid_key(self.index, key, scope, 0) {
+                    let at = match &key.inner {
+                        Shape::Reference(name, _) => name,
+                        _ => location,
+                    };
+                    self.error(
+                        at,
+                        "map keys have to be strings, integers or enums".to_owned(),
+                    );
+                }
+                self.check_shape(location, key, scope);
+                self.check_shape(location, value, scope);
+            }
+            Shape::Reference(name, arguments) => {
+                if !scope.contains(name) {
+                    match self.scopes[self.index].get(name) {
+                        None => self.error(name, format!("unknown type `{}`", name)),
+                        // local references have already been checked by the parser
+                        Some((dependency, model)) if *dependency != self.index => {
+                            let expected = model.type_parameters().len();
+                            if expected != arguments.len() {
+                                self.error(
+                                    name,
+                                    format!(
+                                        "{} expects {} type argument(s), but {} were given",
+                                        name,
+                                        expected,
+                                        arguments.len()
+                                    ),
+                                );
+                            }
+                        }
+                        Some(_) => {}
+                    }
+                }
+
+                for argument in arguments {
+                    self.check_shape(location, argument, scope);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn is_valid_key(&self, module: usize, shape: &Shape<'a>, scope: &[&str], depth: usize) -> bool {
+        // recursive aliases are reported separately
+        if depth > self.modules.len() * 64 {
+            return true;
+        }
+
+        match shape {
+            Shape::String | Shape::Int8 | Shape::Int16 | Shape::Int32 | Shape::Int64 => true,
+            Shape::Reference(name, _) if scope.contains(name) => true,
+            Shape::Reference(name, _) => match self.scopes[module].get(name) {
+                Some((dependency, Model::Alias(inner))) => {
+                    self.is_valid_key(*dependency, &inner.shape.inner, &[], depth + 1)
+                }
+                Some((dependency, Model::External(inner))) => {
+                    self.is_valid_key(*dependency, &inner.shape.inner, &[], depth + 1)
+                }
+                Some((_, Model::Enum(_))) => true,
+                Some(_) => false,
+                // unknown types are reported separately
+                None => true,
+            },
+            _ => false,
+        }
+    }
+
+    /// Follows all references of an alias into other aliases, returning the
+    /// path back to the starting alias if there is one
+    fn find_alias_cycle(
+        &self,
+        module: usize,
+        name: &'a str,
+        path: &mut Vec<(usize, &'a str)>,
+    ) -> Option<Vec<&'a str>> {
+        if let Some(start) = path.first() {
+            if *start == (module, name) {
+                let mut cycle = path.iter().map(|(_, name)| *name).collect::<Vec<_>>();
+                cycle.push(name);
+                return Some(cycle);
+            }
+        }
+
+        if path.contains(&(module, name)) {
+            // a cycle not involving the starting alias, reported from its own aliases
+            return None;
+        }
+
+        let Some((_, Model::Alias(alias))) = self.scopes[module].get(name) else {
+            return None;
+        };
+
+        path.push((module, name));
+
+        let mut references = Vec::new();
+        collect_references(&alias.shape.inner, &alias.type_parameters, &mut references);
+
+        for reference in references {
+            if let Some((dependency, _)) = self.scopes[module].get(reference) {
+                if let Some(cycle) = self.find_alias_cycle(*dependency, reference, path) {
+                    return Some(cycle);
+                }
+            }
+        }
+
+        path.pop();
+
+        None
+    }
+}
+
+fn collect_references<'a>(shape: &Shape<'a>, scope: &[&'a str], references: &mut Vec<&'a str>) {
+    match shape {
+        Shape::Nullable(inner) => collect_references(inner, scope, references),
+        Shape::List(inner) => collect_references(inner, scope, references),
+        Shape::Map(key, value) => {
+            collect_references(key, scope, references);
+            collect_references(value, scope, references);
+        }
+        Shape::Reference(name, arguments) => {
+            if !scope.contains(name) {
+                references.push(name);
+            }
+            for argument in arguments {
+                collect_references(argument, scope, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn module<'a>(path: &'a str, source: &'a str) -> Module<'a> {
+        Module {
+            path: Path::new(path),
+            source,
+            schema: Schema::parse(source).unwrap(),
+            dependencies: Vec::new(),
+            is_complete: true,
+        }
+    }
+
+    fn messages(modules: &[Module]) -> Vec<String> {
+        check(modules)
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    fn check_source(source: &str) -> Vec<String> {
+        messages(&[module("schema.hgen", source)])
+    }
+
+    #[test]
+    fn accepts_valid_schema() {
+        let source = "
+            struct Page<T> {
+              items: List<T>,
+            }
+
+            enum Role {
+              Admin,
+              User,
+            }
+
+            struct User {
+              roles: Map<Role, Page<String>>,
+            }
+
+            service UserService {
+              get(id: Int64) -> User,
+            }
+        ";
+        assert_eq!(check_source(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_unknown_types() {
+        let source = "
+            struct Todo {
+              id: Int64,
+            }
+
+            service TodoService {
+              getAll() -> List<Tood>,
+            }
+        ";
+        assert_eq!(check_source(source), ["unknown type `Tood`"]);
+    }
+
+    #[test]
+    fn reports_duplicate_names() {
+        let source = "
+            struct Todo {
+              title: String,
+              title: String,
+            }
+
+            enum Todo {
+              Open,
+              Open,
+            }
+
+            service TodoService {
+              get(id: Int64, id: Int64) -> Todo,
+              get() -> Todo,
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "duplicate model `Todo`",
+                "duplicate field `title`",
+                "duplicate variant `Open`",
+                "duplicate method `get`",
+                "duplicate input `id`",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_alias_cycles() {
+        let source = "
+            alias A = List<B>;
+            alias B = Map<String, A>;
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "recursive alias: A -> B -> A",
+                "recursive alias: B -> A -> B"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_map_keys() {
+        let source = "
+            struct Point {
+              x: Float64,
+            }
+
+            alias Key = Int32;
+
+            struct Grid {
+              valid: Map<Key, Point>,
+              invalid: Map<Point, Int32>,
+              nested: Map<List<String>, Int32>,
+            }
+        ";
+        assert_eq!(
+            check_source(source),
+            [
+                "map keys have to be strings, integers or enums",
+                "map keys have to be strings, integers or enums",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_arity_of_imported_models() {
+        let common = module("common.hgen", "struct Page<T> {\n  items: List<T>,\n}\n");
+        let mut entry = module(
+            "entry.hgen",
+            "import { Page } from \"./common.hgen\";\n\nalias Pages = Page<String, Int32>;\n",
+        );
+        entry.dependencies.push(1);
+
+        assert_eq!(
+            messages(&[entry, common]),
+            ["Page expects 1 type argument(s), but 2 were given"]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use console::style;
+use std::{
+    env,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+}
+
+/// A 1-based line and column (counted in characters) within a source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Position {
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+
+        Self { line, column }
+    }
+}
+
+impl Span {
+    pub fn from_offsets(source: &str, start: usize, end: usize) -> Self {
+        Self {
+            start: Position::from_offset(source, start),
+            end: Position::from_offset(source, end),
+        }
+    }
+
+    /// Returns the span of `fragment`, which has to be a slice of `source`.
+    /// Since the schema borrows all names from its source, this allows
+    /// locating any name without storing spans in the schema itself.
+    pub fn of(source: &str, fragment: &str) -> Self {
+        let start = (fragment.as_ptr() as usize).wrapping_sub(source.as_ptr() as usize);
+        debug_assert!(start + fragment.len() <= source.len());
+
+        Self::from_offsets(source, start, start + fragment.len())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file: Option<PathBuf>,
+    pub span: Span,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            file: None,
+            span,
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_file(mut self, file: &Path) -> Self {
+        self.file = Some(file.to_path_buf());
+        self
+    }
+
+    /// Renders the diagnostic along with the offending source line,
+    /// underlining the span with carets
+    pub fn render(&self, source: &str) -> String {
+        let mut output = String::new();
+
+        let severity = match self.severity {
+            Severity::Error => style("error").red().bold(),
+        };
+        output.push_str(&format!("{}: {}\n", severity, style(&self.message).bold()));
+
+        let file = match self.file {
+            Some(ref file) => {
+                let relative = env::current_dir()
+                    .ok()
+                    .and_then(|dir| file.strip_prefix(dir).ok().map(Path::to_path_buf));
+                relative.unwrap_or(file.clone()).display().to_string()
+            }
+            None => "<input>".to_owned(),
+        };
+        let gutter = " ".repeat(self.span.start.line.to_string().len());
+
+        output.push_str(&format!(
+            "{}{} {}:{}:{}\n",
+            gutter,
+            style("-->").blue().bold(),
+            file,
+            self.span.start.line,
+            self.span.start.column
+        ));
+
+        let Some(line) = source.lines().nth(self.span.start.line - 1) else {
+            return output;
+        };
+
+        let start = self.span.start.column - 1;
+        let end = if self.span.end.line == self.span.start.line {
+            self.span.end.column - 1
+        } else {
+            line.chars().count()
+        };
+        let underline = format!(
+            "{}{}",
+            " ".repeat(start),
+            "^".repeat(end.saturating_sub(start).max(1))
+        );
+
+        output.push_str(&format!("{} {}\n", gutter, style("|").blue().bold()));
+        output.push_str(&format!(
+            "{} {} {}\n",
+            style(self.span.start.line).blue().bold(),
+            style("|").blue().bold(),
+            line
+        ));
+        output.push_str(&format!(
+            "{} {} {}\n",
+            gutter,
+            style("|").blue().bold(),
+            match self.severity {
+                Severity::Error => style(underline).red().bold(),
+            }
+        ));
+
+        output
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(ref file) = self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(
+            f,
+            "{}:{}: {}",
+            self.span.start.line, self.span.start.column, self.message
+        )
+    }
+}
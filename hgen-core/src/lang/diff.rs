@@ -0,0 +1,682 @@
+//! Compares two versions of a schema, classifying every change by whether it
+//! breaks existing clients on the wire or code written against the generated
+//! output.
+
+use super::{loader::Module, map::OrderedHashMap, schema::*};
+use std::{mem::discriminant, path::Path, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Values encoded by either version can be decoded by the other
+    Wire,
+    /// Code written against the old generated code keeps compiling
+    Source,
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "wire" => Ok(Policy::Wire),
+            "source" => Ok(Policy::Source),
+            _ => Err(format!(
+                "unknown policy `{}`, expected wire or source",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    ModelAdded,
+    ModelRemoved,
+    ModelKindChanged,
+    TypeParametersChanged,
+    FieldAdded,
+    RequiredFieldAdded,
+    FieldRemoved,
+    MadeRequired,
+    MadeNullable,
+    ShapeChanged,
+    VariantAdded,
+    VariantRemoved,
+    UnionTagChanged,
+    ServiceAdded,
+    ServiceRemoved,
+    ServiceRenamed,
+    MethodAdded,
+    MethodRemoved,
+    InputsChanged,
+    OutputChanged,
+}
+
+impl ChangeKind {
+    pub fn id(&self) -> &'static str {
+        match self {
+            ChangeKind::ModelAdded => "model-added",
+            ChangeKind::ModelRemoved => "model-removed",
+            ChangeKind::ModelKindChanged => "model-kind-changed",
+            ChangeKind::TypeParametersChanged => "type-parameters-changed",
+            ChangeKind::FieldAdded => "field-added",
+            ChangeKind::RequiredFieldAdded => "required-field-added",
+            ChangeKind::FieldRemoved => "field-removed",
+            ChangeKind::MadeRequired => "made-required",
+            ChangeKind::MadeNullable => "made-nullable",
+            ChangeKind::ShapeChanged => "shape-changed",
+            ChangeKind::VariantAdded => "variant-added",
+            ChangeKind::VariantRemoved => "variant-removed",
+            ChangeKind::UnionTagChanged => "union-tag-changed",
+            ChangeKind::ServiceAdded => "service-added",
+            ChangeKind::ServiceRemoved => "service-removed",
+            ChangeKind::ServiceRenamed => "service-renamed",
+            ChangeKind::MethodAdded => "method-added",
+            ChangeKind::MethodRemoved => "method-removed",
+            ChangeKind::InputsChanged => "inputs-changed",
+            ChangeKind::OutputChanged => "output-changed",
+        }
+    }
+
+    pub fn is_breaking(&self, policy: Policy) -> bool {
+        match self {
+            ChangeKind::ModelAdded | ChangeKind::ServiceAdded | ChangeKind::MethodAdded => false,
+            // references to removed models are reported as changed shapes
+            ChangeKind::ModelRemoved
+            | ChangeKind::TypeParametersChanged
+            | ChangeKind::FieldAdded => policy == Policy::Source,
+            ChangeKind::UnionTagChanged => policy == Policy::Wire,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// Dotted path of the changed declaration, e.g. `Todo.title`
+    pub path: String,
+    pub message: String,
+}
+
+/// Lists all changes from the `old` to the `new` version of a schema
+pub fn diff(old: &Schema, new: &Schema) -> Vec<Change> {
+    let mut differ = Differ {
+        changes: Vec::new(),
+    };
+    differ.diff_models(old, new);
+    differ.diff_services(old, new);
+    differ.changes
+}
+
+/// Lists all changes from the `old` to the `new` version of an entry schema
+/// and every schema it imports. Imported schemas are matched by their path
+/// relative to the entry schema, and their changes are prefixed with it, e.g.
+/// `common.hgen:UUID`.
+pub fn diff_modules(old: &[Module], new: &[Module]) -> Vec<Change> {
+    let empty = Schema {
+        imports: Vec::new(),
+        models: OrderedHashMap::new(),
+        services: OrderedHashMap::new(),
+    };
+
+    let old_files = relative_paths(old);
+    let new_files = relative_paths(new);
+
+    let mut changes = diff(&old[0].schema, &new[0].schema);
+    for (file, old_module) in old_files.iter().zip(old).skip(1) {
+        let new_schema = match new_files.iter().position(|new_file| new_file == file) {
+            Some(index) => &new[index].schema,
+            None => &empty,
+        };
+        changes.extend(in_file(file, diff(&old_module.schema, new_schema)));
+    }
+    for (file, new_module) in new_files.iter().zip(new).skip(1) {
+        if !old_files.contains(file) {
+            changes.extend(in_file(file, diff(&empty, &new_module.schema)));
+        }
+    }
+
+    changes
+}
+
+/// Returns the path of every module relative to the directory of the entry
+fn relative_paths(modules: &[Module]) -> Vec<String> {
+    let root = modules[0].path.parent().unwrap_or(Path::new(""));
+    modules
+        .iter()
+        .map(|module| {
+            let path = module.path.strip_prefix(root).unwrap_or(module.path);
+            path.to_string_lossy().replace('\\', "/")
+        })
+        .collect()
+}
+
+fn in_file(file: &str, changes: Vec<Change>) -> Vec<Change> {
+    changes
+        .into_iter()
+        .map(|change| Change {
+            kind: change.kind,
+            path: format!("{}:{}", file, change.path),
+            message: format!("{} in {}", change.message, file),
+        })
+        .collect()
+}
+
+struct Differ {
+    changes: Vec<Change>,
+}
+
+impl Differ {
+    fn change(&mut self, kind: ChangeKind, path: String, message: String) {
+        self.changes.push(Change {
+            kind,
+            path,
+            message,
+        });
+    }
+
+    fn diff_models(&mut self, old: &Schema, new: &Schema) {
+        for (name, old_model) in old.models.iter() {
+            match new.models.get(name) {
+                Some(new_model) => self.diff_model(name, old_model, new_model),
+                None => self.change(
+                    ChangeKind::ModelRemoved,
+                    name.to_string(),
+                    format!("{} `{}` was removed", kind_of(old_model), name),
+                ),
+            }
+        }
+
+        for (name, new_model) in new.models.iter() {
+            if old.models.get(name).is_none() {
+                self.change(
+                    ChangeKind::ModelAdded,
+                    name.to_string(),
+                    format!("{} `{}` was added", kind_of(new_model), name),
+                );
+            }
+        }
+    }
+
+    fn diff_model(&mut self, name: &str, old: &Model, new: &Model) {
+        if discriminant(old) != discriminant(new) {
+            self.change(
+                ChangeKind::ModelKindChanged,
+                name.to_string(),
+                format!(
+                    "`{}` changed from {} to {}",
+                    name,
+                    kind_of(old),
+                    kind_of(new)
+                ),
+            );
+            return;
+        }
+
+        if old.type_parameters() != new.type_parameters() {
+            self.change(
+                ChangeKind::TypeParametersChanged,
+                name.to_string(),
+                format!(
+                    "type parameters of `{}` changed from <{}> to <{}>",
+                    name,
+                    old.type_parameters().join(", "),
+                    new.type_parameters().join(", ")
+                ),
+            );
+        }
+
+        match (old, new) {
+            (Model::Struct(old), Model::Struct(new)) => {
+                self.diff_fields(name, &old.fields, &new.fields)
+            }
+            (Model::Enum(old), Model::Enum(new)) => {
+                self.diff_variants(name, &old.fields, &new.fields);
+            }
+            (Model::Union(old), Model::Union(new)) => self.diff_union(name, old, new),
+            (Model::Alias(old), Model::Alias(new)) => {
+                self.diff_shape(name, &old.shape.inner, &new.shape.inner)
+            }
+            (Model::External(old), Model::External(new)) => {
+                self.diff_shape(name, &old.shape.inner, &new.shape.inner)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn diff_union(&mut self, name: &str, old: &Union, new: &Union) {
+        if old.tag() != new.tag() || old.content() != new.content() {
+            self.change(
+                ChangeKind::UnionTagChanged,
+                name.to_string(),
+                format!(
+                    "`{}` is now tagged by `{}` and `{}` instead of `{}` and `{}`",
+                    name,
+                    new.tag(),
+                    new.content(),
+                    old.tag(),
+                    old.content()
+                ),
+            );
+        }
+
+        self.diff_variants(
+            name,
+            &old.variants
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            &new.variants
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+        );
+
+        for (variant, old_variant) in old.variants.iter() {
+            let Some(new_variant) = new.variants.get(variant) else {
+                continue;
+            };
+            let path = format!("{}.{}", name, variant);
+
+            match (old_variant, new_variant) {
+                (Variant::Unit, Variant::Unit) => {}
+                (Variant::Tuple(old), Variant::Tuple(new)) => self.diff_shape(&path, old, new),
+                (Variant::Struct(old), Variant::Struct(new)) => self.diff_fields(&path, old, new),
+                _ => self.change(
+                    ChangeKind::ShapeChanged,
+                    path.clone(),
+                    format!("payload of `{}` changed", path),
+                ),
+            }
+        }
+    }
+
+    fn diff_variants(&mut self, name: &str, old: &[&str], new: &[&str]) {
+        for variant in old {
+            if !new.contains(variant) {
+                self.change(
+                    ChangeKind::VariantRemoved,
+                    format!("{}.{}", name, variant),
+                    format!("variant `{}` was removed from `{}`", variant, name),
+                );
+            }
+        }
+
+        for variant in new {
+            if !old.contains(variant) {
+                self.change(
+                    ChangeKind::VariantAdded,
+                    format!("{}.{}", name, variant),
+                    format!("variant `{}` was added to `{}`", variant, name),
+                );
+            }
+        }
+    }
+
+    fn diff_fields(
+        &mut self,
+        name: &str,
+        old: &OrderedHashMap<&str, Annotated<Shape>>,
+        new: &OrderedHashMap<&str, Annotated<Shape>>,
+    ) {
+        for (field, old_shape) in old.iter() {
+            let path = format!("{}.{}", name, field);
+            match new.get(field) {
+                Some(new_shape) => self.diff_shape(&path, &old_shape.inner, &new_shape.inner),
+                None => self.change(
+                    ChangeKind::FieldRemoved,
+                    path,
+                    format!("field `{}` was removed from `{}`", field, name),
+                ),
+            }
+        }
+
+        for (field, new_shape) in new.iter() {
+            if old.get(field).is_some() {
+                continue;
+            }
+
+            let path = format!("{}.{}", name, field);
+            match new_shape.inner {
+                Shape::Nullable(_) => self.change(
+                    ChangeKind::FieldAdded,
+                    path,
+                    format!("nullable field `{}` was added to `{}`", field, name),
+                ),
+                _ => self.change(
+                    ChangeKind::RequiredFieldAdded,
+                    path,
+                    format!("required field `{}` was added to `{}`", field, name),
+                ),
+            }
+        }
+    }
+
+    fn diff_shape(&mut self, path: &str, old: &Shape, new: &Shape) {
+        let (old, new) = (&strip_metadata(old), &strip_metadata(new));
+        if old == new {
+            return;
+        }
+
+        match (old, new) {
+            (Shape::Nullable(inner), _) if inner.as_ref() == new => self.change(
+                ChangeKind::MadeRequired,
+                path.to_string(),
+                format!("`{}` is no longer nullable", path),
+            ),
+            (_, Shape::Nullable(inner)) if inner.as_ref() == old => self.change(
+                ChangeKind::MadeNullable,
+                path.to_string(),
+                format!("`{}` is now nullable", path),
+            ),
+            _ => self.change(
+                ChangeKind::ShapeChanged,
+                path.to_string(),
+                format!("`{}` changed from `{}` to `{}`", path, old, new),
+            ),
+        }
+    }
+
+    fn diff_services(&mut self, old: &Schema, new: &Schema) {
+        let mut added = new
+            .services
+            .iter()
+            .filter(|(name, _)| old.services.get(*name).is_none())
+            .collect::<Vec<_>>();
+
+        for (name, old_service) in old.services.iter() {
+            if let Some(new_service) = new.services.get(name) {
+                self.diff_service(name, old_service, new_service);
+                continue;
+            }
+
+            // a new service with exactly the same methods is most likely a rename
+            match added
+                .iter()
+                .position(|(_, service)| *service == old_service)
+            {
+                Some(index) => {
+                    let (new_name, _) = added.remove(index);
+                    self.change(
+                        ChangeKind::ServiceRenamed,
+                        name.to_string(),
+                        format!("service `{}` was renamed to `{}`", name, new_name),
+                    );
+                }
+                None => self.change(
+                    ChangeKind::ServiceRemoved,
+                    name.to_string(),
+                    format!("service `{}` was removed", name),
+                ),
+            }
+        }
+
+        for (name, _) in added {
+            self.change(
+                ChangeKind::ServiceAdded,
+                name.to_string(),
+                format!("service `{}` was added", name),
+            );
+        }
+    }
+
+    fn diff_service(&mut self, name: &str, old: &Service, new: &Service) {
+        for (method, old_method) in old.methods.iter() {
+            let path = format!("{}.{}", name, method);
+            let Some(new_method) = new.methods.get(method) else {
+                self.change(
+                    ChangeKind::MethodRemoved,
+                    path,
+                    format!("method `{}` was removed from `{}`", method, name),
+                );
+                continue;
+            };
+
+            let (old_method, new_method) = (&old_method.inner, &new_method.inner);
+            if strip_inputs(&old_method.inputs) != strip_inputs(&new_method.inputs) {
+                self.change(
+                    ChangeKind::InputsChanged,
+                    path.clone(),
+                    format!(
+                        "inputs of `{}` changed from ({}) to ({})",
+                        path,
+                        format_inputs(&old_method.inputs),
+                        format_inputs(&new_method.inputs)
+                    ),
+                );
+            }
+            if old_method.output.as_deref().map(strip_metadata)
+                != new_method.output.as_deref().map(strip_metadata)
+            {
+                self.change(
+                    ChangeKind::OutputChanged,
+                    path.clone(),
+                    format!(
+                        "output of `{}` changed from `{}` to `{}`",
+                        path,
+                        format_output(&old_method.output),
+                        format_output(&new_method.output)
+                    ),
+                );
+            }
+        }
+
+        for (method, _) in new.methods.iter() {
+            if old.methods.get(method).is_none() {
+                self.change(
+                    ChangeKind::MethodAdded,
+                    format!("{}.{}", name, method),
+                    format!("method `{}` was added to `{}`", method, name),
+                );
+            }
+        }
+    }
+}
+
+fn kind_of(model: &Model) -> &'static str {
+    match model {
+        Model::Struct(_) => "struct",
+        Model::Enum(_) => "enum",
+        Model::Union(_) => "union",
+        Model::Alias(_) => "alias",
+        Model::External(_) => "external alias",
+    }
+}
+
+/// Drops the metadata of nested shapes, which does not change the shape
+fn strip_metadata<'a>(shape: &Shape<'a>) -> Shape<'a> {
+    let strip = |shape: &Annotated<'a, Shape<'a>>| Annotated::new(strip_metadata(shape));
+    match shape {
+        Shape::List(inner) => Shape::List(Box::new(strip(inner))),
+        Shape::Map(key, value) => Shape::Map(Box::new(strip(key)), Box::new(strip(value))),
+        Shape::Nullable(inner) => Shape::Nullable(Box::new(strip_metadata(inner))),
+        Shape::Reference(name, arguments) => {
+            Shape::Reference(name, arguments.iter().map(strip).collect())
+        }
+        shape => shape.clone(),
+    }
+}
+
+fn strip_inputs<'a>(
+    inputs: &OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>,
+) -> Vec<(&'a str, Shape<'a>)> {
+    inputs
+        .iter()
+        .map(|(name, shape)| (*name, strip_metadata(shape)))
+        .collect()
+}
+
+fn format_inputs(inputs: &OrderedHashMap<&str, Annotated<Shape>>) -> String {
+    inputs
+        .iter()
+        .map(|(name, shape)| format!("{}: {}", name, shape))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_output(output: &Option<Annotated<Shape>>) -> String {
+    match output {
+        Some(shape) => shape.to_string(),
+        None => "Unit".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes(old: &str, new: &str) -> Vec<(String, ChangeKind)> {
+        let old = Schema::parse(old).unwrap();
+        let new = Schema::parse(new).unwrap();
+        diff(&old, &new)
+            .into_iter()
+            .map(|change| (change.path, change.kind))
+            .collect()
+    }
+
+    const TODO: &str = "
+        struct Todo {
+          id: Int64,
+          title: String,
+          note: String?,
+        }
+
+        service TodoService {
+          get(id: Int64) -> Todo,
+        }
+    ";
+
+    #[test]
+    fn finds_no_changes_in_same_schema() {
+        assert!(changes(TODO, TODO).is_empty());
+    }
+
+    #[test]
+    fn classifies_added_declarations() {
+        let new = "
+            struct Todo {
+              id: Int64,
+              title: String,
+              note: String?,
+              dueAt: String?,
+            }
+
+            struct Tag {
+              name: String,
+            }
+
+            service TodoService {
+              get(id: Int64) -> Todo,
+              getAll() -> List<Todo>,
+            }
+        ";
+        let changes = changes(TODO, new);
+        assert_eq!(
+            changes,
+            [
+                ("Todo.dueAt".to_owned(), ChangeKind::FieldAdded),
+                ("Tag".to_owned(), ChangeKind::ModelAdded),
+                ("TodoService.getAll".to_owned(), ChangeKind::MethodAdded),
+            ]
+        );
+
+        for (_, kind) in changes {
+            assert!(!kind.is_breaking(Policy::Wire));
+        }
+        assert!(ChangeKind::FieldAdded.is_breaking(Policy::Source));
+        assert!(!ChangeKind::ModelAdded.is_breaking(Policy::Source));
+    }
+
+    #[test]
+    fn classifies_changed_fields() {
+        let new = "
+            struct Todo {
+              id: String,
+              title: String?,
+              note: String,
+              done: Bool,
+            }
+
+            service TodoService {
+              get(id: Int64) -> Todo,
+            }
+        ";
+        let changes = changes(TODO, new);
+        assert_eq!(
+            changes,
+            [
+                ("Todo.id".to_owned(), ChangeKind::ShapeChanged),
+                ("Todo.title".to_owned(), ChangeKind::MadeNullable),
+                ("Todo.note".to_owned(), ChangeKind::MadeRequired),
+                ("Todo.done".to_owned(), ChangeKind::RequiredFieldAdded),
+            ]
+        );
+
+        for (_, kind) in changes {
+            assert!(kind.is_breaking(Policy::Wire));
+            assert!(kind.is_breaking(Policy::Source));
+        }
+    }
+
+    #[test]
+    fn classifies_removed_declarations() {
+        let new = "
+            struct Todo {
+              id: Int64,
+              title: String,
+            }
+
+            service TodoService {
+              get(id: String) -> Todo,
+            }
+        ";
+        assert_eq!(
+            changes(TODO, new),
+            [
+                ("Todo.note".to_owned(), ChangeKind::FieldRemoved),
+                ("TodoService.get".to_owned(), ChangeKind::InputsChanged),
+            ]
+        );
+
+        assert_eq!(
+            changes(TODO, "struct Todo {\n  id: Int64,\n}\n")
+                .into_iter()
+                .map(|(_, kind)| kind)
+                .collect::<Vec<_>>(),
+            [
+                ChangeKind::FieldRemoved,
+                ChangeKind::FieldRemoved,
+                ChangeKind::ServiceRemoved
+            ]
+        );
+        assert!(!ChangeKind::ModelRemoved.is_breaking(Policy::Wire));
+        assert!(ChangeKind::ModelRemoved.is_breaking(Policy::Source));
+    }
+
+    #[test]
+    fn classifies_union_tag_changes() {
+        let old = "union Shape {\n  Circle(Float64),\n}\n";
+        let new = "union Shape {\n  Circle(Float64),\n} & { tag: \"kind\" }\n";
+        let changes = changes(old, new);
+        assert_eq!(changes, [("Shape".to_owned(), ChangeKind::UnionTagChanged)]);
+        assert!(changes[0].1.is_breaking(Policy::Wire));
+        assert!(!changes[0].1.is_breaking(Policy::Source));
+    }
+
+    #[test]
+    fn ignores_metadata_of_nested_shapes_and_inputs() {
+        let new = "
+            struct Todo {
+              id: Int64,
+              title: String,
+              note: String? & { description: \"Free text\" },
+            }
+
+            service TodoService {
+              get(id: Int64 & { min: 1 }) -> Todo & { description: \"The todo\" },
+            }
+        ";
+        assert!(changes(TODO, new).is_empty());
+    }
+}
@@ -0,0 +1,608 @@
+//! Canonical formatting of schemas. Declarations are printed from the parsed
+//! schema, while comments are collected from the source separately and put
+//! back in front of (or behind) the closest declaration.
+
+use super::{
+    diagnostic::Diagnostic,
+    map::OrderedHashMap,
+    parser::{Rule, SchemaParser},
+    schema::*,
+};
+use pest::{
+    iterators::{Pair, Pairs},
+    Parser,
+};
+use std::collections::HashMap;
+
+const WIDTH: usize = 80;
+const INDENT: &str = "  ";
+
+/// Formats a schema, failing if it cannot be parsed
+pub fn format_schema(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let schema = Schema::parse(source)?;
+    let pairs = SchemaParser::parse(Rule::hGEN, source).unwrap();
+
+    let mut formatter = Formatter {
+        source,
+        comments: find_comments(source),
+        next: 0,
+        variant_ends: find_variant_ends(pairs.clone()),
+        previous_item: 0,
+        output: String::new(),
+    };
+
+    let mut previous = None;
+    let mut imports = schema.imports.iter();
+    let pairs = pairs
+        .filter(|pair| pair.as_rule() != Rule::EOI)
+        .collect::<Vec<_>>();
+    let starts = pairs
+        .iter()
+        .skip(1)
+        .map(|pair| pair.as_span().start())
+        .chain([source.len()])
+        .collect::<Vec<_>>();
+
+    for (pair, next) in pairs.into_iter().zip(starts) {
+        let rule = pair.as_rule();
+
+        let span = pair.as_span();
+        match previous {
+            None => {}
+            Some(Rule::Import) if rule == Rule::Import => {}
+            Some(_) => formatter.output.push('\n'),
+        }
+        formatter.leading(span.start(), "");
+
+        match rule {
+            Rule::Import => formatter.import(imports.next().unwrap()),
+            Rule::Model => {
+                let name = declared_name(pair.into_inner().next().unwrap());
+                let (name, model) = find(&schema.models, name);
+                formatter.model(name, model, span.end());
+            }
+            Rule::Service => {
+                let name = declared_name(pair);
+                let (name, service) = find(&schema.services, name);
+                formatter.service(name, service, span.end());
+            }
+            _ => unreachable!("unexpected top-level rule: {:?}", rule),
+        }
+
+        formatter.trailing(next);
+        formatter.output.push('\n');
+        previous = Some(rule);
+    }
+
+    if formatter.next < formatter.comments.len() {
+        if previous.is_some() {
+            formatter.output.push('\n');
+        }
+        formatter.leading(source.len(), "");
+    }
+
+    Ok(formatter.output)
+}
+
+/// Formats a single model without any comments
+pub fn format_model(name: &str, model: &Model) -> String {
+    let mut formatter = Formatter {
+        source: "",
+        comments: Vec::new(),
+        next: 0,
+        variant_ends: HashMap::new(),
+        previous_item: 0,
+        output: String::new(),
+    };
+    formatter.model(name, model, 0);
+    formatter.output
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Comment<'a> {
+    start: usize,
+    text: &'a str,
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    comments: Vec<Comment<'a>>,
+    /// Index of the first comment which has not been printed yet
+    next: usize,
+    /// End offsets of union variants, keyed by the address of their name
+    variant_ends: HashMap<usize, usize>,
+    /// Start of the last item printed, since only blank lines after it
+    /// separate the following item from it
+    previous_item: usize,
+    output: String,
+}
+
+impl<'a> Formatter<'a> {
+    fn offset(&self, name: &str) -> usize {
+        (name.as_ptr() as usize).wrapping_sub(self.source.as_ptr() as usize)
+    }
+
+    /// Column at which the next character will be printed
+    fn column(&self) -> usize {
+        let line_start = self.output.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        self.output[line_start..].chars().count()
+    }
+
+    /// Prints all comments before `offset` on their own lines, keeping a
+    /// single blank line in front of items which had one
+    fn leading(&mut self, offset: usize, indent: &str) {
+        while let Some(comment) = self.comments.get(self.next) {
+            if comment.start >= offset {
+                break;
+            }
+            self.next += 1;
+
+            if self.has_blank_line_before(comment.start) && self.needs_separator() {
+                self.output.push('\n');
+            }
+            self.output.push_str(indent);
+            self.output.push_str(comment.text);
+            self.output.push('\n');
+        }
+    }
+
+    /// Prints comments which follow code on the same line, up to `offset`
+    fn trailing(&mut self, offset: usize) {
+        while let Some(comment) = self.comments.get(self.next) {
+            let line_start = self.source[..comment.start]
+                .rfind('\n')
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            let own_line = self.source[line_start..comment.start].trim().is_empty();
+            if comment.start >= offset || own_line {
+                break;
+            }
+            self.next += 1;
+
+            self.output.push(' ');
+            self.output.push_str(comment.text);
+        }
+    }
+
+    /// Starts a new item within a block, along with its comments
+    fn item(&mut self, offset: usize, indent: &str) {
+        if self.has_blank_line_before(self.item_start(offset)) && self.needs_separator() {
+            self.output.push('\n');
+        }
+        self.leading(offset, indent);
+        self.output.push_str(indent);
+        self.previous_item = offset;
+    }
+
+    /// Ends an item within a block, printing its trailing comments
+    fn end_item(&mut self, next: usize) {
+        self.output.push(',');
+        self.trailing(next);
+        self.output.push('\n');
+    }
+
+    /// Start of an item including its leading comments
+    fn item_start(&self, offset: usize) -> usize {
+        match self.comments.get(self.next) {
+            Some(comment) if comment.start < offset => comment.start,
+            _ => offset,
+        }
+    }
+
+    fn has_blank_line_before(&self, offset: usize) -> bool {
+        if offset > self.source.len() {
+            return false;
+        }
+        let before = &self.source[..offset];
+        let Some(line_start) = before.rfind('\n') else {
+            return false;
+        };
+        let previous = &before[..line_start];
+        let previous_line_start = previous.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        previous_line_start >= self.previous_item
+            && previous[previous_line_start..].trim().is_empty()
+            && !previous.trim().is_empty()
+    }
+
+    /// Whether the output is in the middle of a block, where blank lines are kept
+    fn needs_separator(&self) -> bool {
+        !self.output.is_empty() && !self.output.ends_with("{\n") && !self.output.ends_with("\n\n")
+    }
+
+    fn import(&mut self, import: &Import) {
+        if import.is_wildcard {
+            self.output.push_str(&format!("import \"{}\";", import.path));
+        } else {
+            self.output.push_str(&format!(
+                "import {{ {} }} from \"{}\";",
+                import.names.join(", "),
+                import.path
+            ));
+        }
+    }
+
+    fn model(&mut self, name: &str, model: &Model, end: usize) {
+        let parameters = match model.type_parameters() {
+            [] => String::new(),
+            parameters => format!("<{}>", parameters.join(", ")),
+        };
+
+        match model {
+            Model::Struct(inner) => {
+                self.output
+                    .push_str(&format!("struct {}{} ", name, parameters));
+                self.fields(&inner.fields, end, "");
+            }
+            Model::Enum(inner) => {
+                self.output.push_str(&format!("enum {} {{", name));
+                if !inner.fields.is_empty() {
+                    self.output.push('\n');
+                }
+                for (i, variant) in inner.fields.iter().enumerate() {
+                    self.item(self.offset(variant), INDENT);
+                    self.output.push_str(variant);
+                    self.end_item(self.next_offset(inner.fields.get(i + 1).copied(), end));
+                }
+                self.close(end, "");
+            }
+            Model::Union(inner) => {
+                self.output.push_str(&format!("union {} {{", name));
+                if !inner.variants.is_empty() {
+                    self.output.push('\n');
+                }
+                let variants = inner.variants.iter().collect::<Vec<_>>();
+                for (i, (name, variant)) in variants.iter().enumerate() {
+                    let next = self.next_offset(variants.get(i + 1).map(|(name, _)| **name), end);
+                    self.item(self.offset(name), INDENT);
+                    self.output.push_str(name);
+                    match variant {
+                        Variant::Unit => {}
+                        Variant::Tuple(shape) => self.output.push_str(&format!("({})", shape)),
+                        Variant::Struct(fields) => {
+                            let end = self.variant_ends.get(&(name.as_ptr() as usize));
+                            self.output.push(' ');
+                            self.fields(fields, end.copied().unwrap_or(next), INDENT);
+                        }
+                    }
+                    self.end_item(next);
+                }
+                self.close(end, "");
+                self.metadata(&inner.metadata, " & ", "", 0);
+            }
+            Model::Alias(inner) => {
+                self.output.push_str(&format!(
+                    "alias {}{} = {}",
+                    name, parameters, inner.shape.inner
+                ));
+                self.metadata(&inner.shape.metadata, " & ", "", 1);
+                self.output.push(';');
+            }
+            Model::External(inner) => {
+                self.output
+                    .push_str(&format!("external alias {} = {}", name, inner.shape.inner));
+                self.metadata(&inner.shape.metadata, " & ", "", 1);
+                self.output.push(';');
+            }
+        }
+    }
+
+    fn fields(&mut self, fields: &OrderedHashMap<&str, Annotated<Shape>>, end: usize, indent: &str) {
+        let fields = fields.iter().collect::<Vec<_>>();
+        if fields.is_empty() {
+            self.output.push_str("{}");
+            return;
+        }
+
+        // short fields without metadata or comments, like union variants, stay on one line
+        let inline = fields
+            .iter()
+            .map(|(name, shape)| format!("{}: {}", name, shape.inner))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let has_comments = self
+            .comments
+            .get(self.next)
+            .is_some_and(|comment| comment.start < end);
+        if !indent.is_empty()
+            && !has_comments
+            && fields.iter().all(|(_, shape)| shape.metadata.is_empty())
+            && self.column() + inline.len() + 5 <= WIDTH
+        {
+            self.output.push_str(&format!("{{ {} }}", inline));
+            return;
+        }
+
+        let inner_indent = format!("{}{}", indent, INDENT);
+        self.output.push_str("{\n");
+        for (i, (name, shape)) in fields.iter().enumerate() {
+            let next = self.next_offset(fields.get(i + 1).map(|(name, _)| **name), end);
+            self.item(self.offset(name), &inner_indent);
+            self.output.push_str(&format!("{}: {}", name, shape.inner));
+            self.metadata(&shape.metadata, " & ", &inner_indent, 1);
+            self.end_item(next);
+        }
+        self.close(end, indent);
+    }
+
+    fn service(&mut self, name: &str, service: &Service, end: usize) {
+        self.output.push_str(&format!("service {} {{\n", name));
+
+        let methods = service.methods.iter().collect::<Vec<_>>();
+        for (i, (name, method)) in methods.iter().enumerate() {
+            let next = self.next_offset(methods.get(i + 1).map(|(name, _)| **name), end);
+            self.item(self.offset(name), INDENT);
+
+            let inputs = method
+                .inner
+                .inputs
+                .iter()
+                .map(|(name, shape)| format!("{}: {}", name, shape))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.output.push_str(&format!("{}({})", name, inputs));
+            if let Some(ref output) = method.inner.output {
+                self.output.push_str(&format!(" -> {}", output));
+            }
+            self.metadata(&method.metadata, " ", INDENT, 1);
+            self.end_item(next);
+        }
+
+        self.close(end, "");
+    }
+
+    /// Prints the comments left within a block and closes it
+    fn close(&mut self, end: usize, indent: &str) {
+        if self.output.ends_with('{') {
+            self.output.push('}');
+            return;
+        }
+        self.leading(end, &format!("{}{}", indent, INDENT));
+        self.output.push_str(indent);
+        self.output.push('}');
+    }
+
+    /// Offset of the next item in a block, or the end of the block
+    fn next_offset(&self, next: Option<&str>, end: usize) -> usize {
+        next.map(|name| self.offset(name)).unwrap_or(end)
+    }
+
+    fn metadata(
+        &mut self,
+        metadata: &OrderedHashMap<&str, Literal>,
+        separator: &str,
+        indent: &str,
+        suffix: usize,
+    ) {
+        if metadata.is_empty() {
+            return;
+        }
+        self.output.push_str(separator);
+        let column = self.column();
+        let literal = format_literal(&Literal::Object(metadata.clone()), indent, column, suffix);
+        self.output.push_str(&literal);
+    }
+}
+
+/// Formats a literal starting at `column`, breaking objects and arrays onto
+/// multiple lines if they do not fit
+fn format_literal(literal: &Literal, indent: &str, column: usize, suffix: usize) -> String {
+    let inline = literal.to_string();
+    if column + inline.chars().count() + suffix <= WIDTH {
+        return inline;
+    }
+
+    let inner_indent = format!("{}{}", indent, INDENT);
+    let (open, close, entries) = match literal {
+        Literal::Object(fields) if !fields.is_empty() => (
+            '{',
+            '}',
+            fields
+                .iter()
+                .map(|(key, value)| {
+                    let column = inner_indent.len() + key.len() + 2;
+                    format!(
+                        "{}: {}",
+                        key,
+                        format_literal(value, &inner_indent, column, 1)
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Literal::Array(values) if !values.is_empty() => (
+            '[',
+            ']',
+            values
+                .iter()
+                .map(|value| format_literal(value, &inner_indent, inner_indent.len(), 1))
+                .collect(),
+        ),
+        _ => return inline,
+    };
+
+    let mut output = format!("{}\n", open);
+    for entry in entries {
+        output.push_str(&format!("{}{},\n", inner_indent, entry));
+    }
+    output.push_str(&format!("{}{}", indent, close));
+    output
+}
+
+/// Finds all comments, skipping over string and char literals
+fn find_comments(source: &str) -> Vec<Comment<'_>> {
+    let mut comments = Vec::new();
+    let bytes = source.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = source[i..].find('\n').map(|n| i + n).unwrap_or(source.len());
+                comments.push(Comment {
+                    start: i,
+                    text: source[i..end].trim_end(),
+                });
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = source[i + 2..]
+                    .find("*/")
+                    .map(|n| i + 2 + n + 2)
+                    .unwrap_or(source.len());
+                comments.push(Comment {
+                    start: i,
+                    text: &source[i..end],
+                });
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+
+    comments
+}
+
+fn find_variant_ends(pairs: Pairs<'_, Rule>) -> HashMap<usize, usize> {
+    let mut ends = HashMap::new();
+    let mut queue = pairs.collect::<Vec<_>>();
+
+    while let Some(pair) = queue.pop() {
+        if pair.as_rule() == Rule::UnionVariant {
+            let name = pair.clone().into_inner().next().unwrap().as_str();
+            ends.insert(name.as_ptr() as usize, pair.as_span().end());
+        }
+        queue.extend(pair.into_inner());
+    }
+
+    ends
+}
+
+fn declared_name(pair: Pair<'_, Rule>) -> &str {
+    pair.into_inner().next().unwrap().as_str()
+}
+
+/// Finds a declaration by its name, which has to be a slice of the same source
+fn find<'m, 'a, T>(map: &'m OrderedHashMap<&'a str, T>, name: &str) -> (&'a str, &'m T) {
+    map.iter()
+        .find(|(declared, _)| declared.as_ptr() == name.as_ptr())
+        .map(|(declared, value)| (*declared, value))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLES: [&str; 6] = [
+        include_str!("../../../examples/todo/todo.hgen"),
+        include_str!("../../../examples/rest/orders.hgen"),
+        include_str!("../../../examples/validation/contact.hgen"),
+        include_str!("../../../examples/multi-file/common.hgen"),
+        include_str!("../../../examples/multi-file/private.hgen"),
+        include_str!("../../../examples/multi-file/public.hgen"),
+    ];
+
+    #[test]
+    fn keeps_examples_unchanged() {
+        for example in EXAMPLES {
+            assert_eq!(format_schema(example).unwrap(), example);
+        }
+    }
+
+    #[test]
+    fn is_idempotent() {
+        let source = "
+            // roles of a user
+            enum Role {
+                Admin,
+                User,
+            }
+            union Shape {
+                Circle(Float64),   Square { side: Float64 },
+                Empty,
+            } & { tag: \"kind\" }
+
+            struct User {
+                name: String & { minLength: 1, pattern: \"[a-z]+\" },   // trailing
+                roles: List<Role>,
+            }
+        ";
+        let expected = "\
+// roles of a user
+enum Role {
+  Admin,
+  User,
+}
+
+union Shape {
+  Circle(Float64),
+  Square { side: Float64 },
+  Empty,
+} & { tag: \"kind\" }
+
+struct User {
+  name: String & { minLength: 1, pattern: \"[a-z]+\" }, // trailing
+  roles: List<Role>,
+}
+";
+
+        let formatted = format_schema(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_schema(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn keeps_one_line_blocks_compact() {
+        let source = "enum Role { Admin, User, }\n\nunion Shape { Circle(Float64), Empty, }\n";
+        let expected = "\
+enum Role {
+  Admin,
+  User,
+}
+
+union Shape {
+  Circle(Float64),
+  Empty,
+}
+";
+
+        assert_eq!(format_schema(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_metadata_of_nested_shapes_and_inputs() {
+        let source = "
+            union Value {
+                Text(String&{maxLength:10}),
+            }
+            struct Page<T> { items: List<T & {description:\"An item\"}>, tags: Map<String, Int32&{min:0}> }
+            service PageService {
+                get(size: Int32&{ min: 1 }, after: String?) -> Page<String & {minLength: 1}>,
+            }
+        ";
+        let expected = "\
+union Value {
+  Text(String & { maxLength: 10 }),
+}
+
+struct Page<T> {
+  items: List<T & { description: \"An item\" }>,
+  tags: Map<String, Int32 & { min: 0 }>,
+}
+
+service PageService {
+  get(size: Int32 & { min: 1 }, after: String?) -> Page<String & { minLength: 1 }>,
+}
+";
+
+        let formatted = format_schema(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_schema(&formatted).unwrap(), formatted);
+    }
+}
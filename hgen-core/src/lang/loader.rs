@@ -0,0 +1,229 @@
+use super::{
+    checker,
+    diagnostic::{Diagnostic, Span},
+    schema::Schema,
+};
+use anyhow::Context;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub source: String,
+}
+
+/// All source files reachable from an entry schema through its imports.
+/// The entry schema is always the first file.
+#[derive(Debug, Clone)]
+pub struct Sources {
+    pub files: Vec<SourceFile>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Module<'a> {
+    pub path: &'a Path,
+    pub source: &'a str,
+    pub schema: Schema<'a>,
+    /// Index of the imported module for each import of the schema
+    pub dependencies: Vec<usize>,
+    /// Whether all imports could be resolved. Incomplete modules may refer to
+    /// models which are unknown, so they are not worth checking.
+    pub is_complete: bool,
+}
+
+impl Sources {
+    pub fn load(entry: &Path) -> anyhow::Result<Self> {
+        let entry = fs::canonicalize(entry)
+            .with_context(|| format!("failed to open {}", entry.display()))?;
+        Self::load_all(vec![entry], |path| fs::read_to_string(path))
+    }
+
+    /// Loads several entry schemas along with their imports, reading files
+    /// through `read` so unsaved contents can be used instead of the disk.
+    /// Entries have to be canonical paths.
+    pub fn load_all(
+        entries: Vec<PathBuf>,
+        read: impl Fn(&Path) -> io::Result<String>,
+    ) -> anyhow::Result<Self> {
+        let mut files: Vec<SourceFile> = Vec::new();
+        let mut queue = entries;
+        queue.reverse();
+
+        while let Some(path) = queue.pop() {
+            if files.iter().any(|file| file.path == path) {
+                continue;
+            }
+
+            let source =
+                read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+
+            // files that fail to parse or resolve are reported once the sources are parsed
+            if let Ok(schema) = Schema::parse(&source) {
+                for import in schema.imports {
+                    if let Ok(import_path) = resolve_import(&path, import.path) {
+                        queue.push(import_path);
+                    }
+                }
+            }
+
+            files.push(SourceFile { path, source });
+        }
+
+        Ok(Self { files })
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&SourceFile> {
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// Parses every source file and fills in the names of wildcard imports
+    pub fn parse(&self) -> Result<Vec<Module<'_>>, Vec<Diagnostic>> {
+        let (modules, diagnostics) = self.parse_all();
+
+        if diagnostics.is_empty() {
+            Ok(modules)
+        } else {
+            Err(diagnostics)
+        }
+    }
+
+    /// Parses and checks every source file, returning the modules only if
+    /// there are no diagnostics
+    pub fn compile(&self) -> Result<Vec<Module<'_>>, Vec<Diagnostic>> {
+        let modules = self.parse()?;
+
+        let diagnostics = checker::check(&modules);
+        if !diagnostics.is_empty() {
+            return Err(diagnostics);
+        }
+
+        Ok(modules)
+    }
+
+    /// Renders diagnostics along with the lines of the files they point at
+    pub fn render(&self, diagnostics: &[Diagnostic]) -> String {
+        let mut output = String::new();
+
+        for diagnostic in diagnostics {
+            let source = diagnostic
+                .file
+                .as_deref()
+                .and_then(|file| self.get(file))
+                .map(|file| file.source.as_str())
+                .unwrap_or_default();
+
+            output.push_str(&diagnostic.render(source));
+            output.push('\n');
+        }
+
+        output
+    }
+
+    /// Parses every source file like [`Sources::parse`], but keeps going on
+    /// errors, returning all files which could be parsed. Imports of files
+    /// which failed to parse are left out, marking the importer incomplete.
+    pub fn parse_all(&self) -> (Vec<Module<'_>>, Vec<Diagnostic>) {
+        let mut modules = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        for file in self.files.iter() {
+            match Schema::parse(&file.source) {
+                Ok(schema) => modules.push(Module {
+                    path: &file.path,
+                    source: &file.source,
+                    schema,
+                    dependencies: Vec::new(),
+                    is_complete: true,
+                }),
+                Err(errors) => diagnostics.extend(
+                    errors
+                        .into_iter()
+                        .map(|diagnostic| diagnostic.with_file(&file.path)),
+                ),
+            }
+        }
+
+        for i in 0..modules.len() {
+            let mut j = 0;
+            while j < modules[i].schema.imports.len() {
+                let import_path = modules[i].schema.imports[j].path;
+                let is_wildcard = modules[i].schema.imports[j].is_wildcard;
+
+                let resolved = resolve_import(modules[i].path, import_path).ok();
+                let dependency = resolved
+                    .as_ref()
+                    .and_then(|path| modules.iter().position(|module| module.path == path));
+
+                let Some(dependency) = dependency else {
+                    if resolved.is_some_and(|path| self.get(&path).is_some()) {
+                        // the imported file has errors of its own
+                        modules[i].schema.imports.remove(j);
+                        modules[i].is_complete = false;
+                        continue;
+                    }
+
+                    diagnostics.push(
+                        Diagnostic::error(
+                            Span::of(modules[i].source, import_path),
+                            format!("failed to resolve import {}", import_path),
+                        )
+                        .with_file(modules[i].path),
+                    );
+                    modules[i].schema.imports.remove(j);
+                    modules[i].is_complete = false;
+                    continue;
+                };
+
+                modules[i].dependencies.push(dependency);
+                j += 1;
+
+                if !is_wildcard {
+                    continue;
+                }
+
+                let names = modules[dependency]
+                    .schema
+                    .models
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect();
+
+                modules[i].schema.imports[j - 1].names = names;
+            }
+        }
+
+        (modules, diagnostics)
+    }
+}
+
+/// Adds all schemas within a directory, skipping hidden and build directories
+pub fn find_schemas(directory: &Path, schemas: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" && name != "node_modules" {
+                find_schemas(&path, schemas);
+            }
+        } else if name.ends_with(".hgen") {
+            if let Ok(path) = fs::canonicalize(path) {
+                schemas.push(path);
+            }
+        }
+    }
+}
+
+/// Resolves an import path relative to the directory of the importing file
+pub fn resolve_import(importer: &Path, path: &str) -> anyhow::Result<PathBuf> {
+    let path = importer.parent().unwrap_or(Path::new("")).join(path);
+    fs::canonicalize(&path).with_context(|| format!("failed to resolve import {}", path.display()))
+}
@@ -1,12 +1,12 @@
-use std::{collections::HashMap, fmt::Debug, hash::Hash};
+use std::{borrow::Borrow, collections::HashMap, fmt::Debug, hash::Hash};
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct OrderedHashMap<K, V>
 where
     K: Hash + Eq + Clone,
 {
-    inner: HashMap<K, V>,
-    order: Vec<K>,
+    index: HashMap<K, usize>,
+    entries: Vec<(K, V)>,
 }
 
 impl<K: Debug + Hash + Eq + Clone, V: Debug> Debug for OrderedHashMap<K, V> {
@@ -24,20 +24,34 @@ impl<K: Hash + Eq + Clone, V> Default for OrderedHashMap<K, V> {
 impl<K: Hash + Eq + Clone, V> OrderedHashMap<K, V> {
     pub fn new() -> Self {
         Self {
-            inner: HashMap::new(),
-            order: Vec::new(),
+            index: HashMap::new(),
+            entries: Vec::new(),
         }
     }
 
+    /// Inserts a value, keeping its position in the iteration order. Keys
+    /// inserted more than once are iterated once per insertion along with
+    /// their own value, so duplicates can be reported by the checker. Lookups
+    /// return the last inserted value.
     pub fn insert(&mut self, key: K, value: V) {
-        self.inner.insert(key.clone(), value);
-        self.order.push(key);
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(key).map(|index| &self.entries[*index].1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
-        self.order
-            .iter()
-            .map(move |key| (key, self.inner.get(key).unwrap()))
+        self.entries.iter().map(|(key, value)| (key, value))
     }
 }
 
@@ -0,0 +1,8 @@
+pub mod checker;
+pub mod diagnostic;
+pub mod diff;
+pub mod format;
+pub mod loader;
+pub mod map;
+pub mod parser;
+pub mod schema;
@@ -0,0 +1,619 @@
+use super::{
+    diagnostic::{Diagnostic, Position, Span},
+    map::OrderedHashMap,
+    schema::*,
+};
+use pest::{error::LineColLocation, iterators::Pair, Parser};
+use pest_derive::Parser;
+
+#[derive(Parser)]
+#[grammar = "hgen.pest"]
+pub struct SchemaParser;
+
+type Result<T> = std::result::Result<T, Diagnostic>;
+
+pub fn parse_schema(source: &str) -> std::result::Result<Schema<'_>, Vec<Diagnostic>> {
+    let pairs = SchemaParser::parse(Rule::hGEN, source).map_err(|e| vec![syntax_error(e)])?;
+    let mut imports = Vec::new();
+    let mut models = OrderedHashMap::new();
+    let mut services = OrderedHashMap::new();
+    let mut diagnostics = Vec::new();
+
+    for pair in pairs {
+        let result = match pair.as_rule() {
+            Rule::Import => parse_import(pair).map(|import| imports.push(import)),
+            Rule::Model => parse_model(pair.into_inner().next().unwrap())
+                .map(|(name, model)| models.insert(name, model)),
+            Rule::Service => {
+                parse_service(pair).map(|(name, service)| services.insert(name, service))
+            }
+            Rule::EOI => break,
+            _ => unreachable!("unexpected top-level rule: {:?}", pair.as_rule()),
+        };
+
+        if let Err(diagnostic) = result {
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    let schema = Schema {
+        imports,
+        models,
+        services,
+    };
+
+    diagnostics.extend(check_arity(source, &schema));
+
+    if diagnostics.is_empty() {
+        Ok(schema)
+    } else {
+        Err(diagnostics)
+    }
+}
+
+fn syntax_error(error: pest::error::Error<Rule>) -> Diagnostic {
+    let span = match error.line_col {
+        LineColLocation::Pos((line, column)) => Span {
+            start: Position { line, column },
+            end: Position {
+                line,
+                column: column + 1,
+            },
+        },
+        LineColLocation::Span((start_line, start_column), (end_line, end_column)) => Span {
+            start: Position {
+                line: start_line,
+                column: start_column,
+            },
+            end: Position {
+                line: end_line,
+                column: end_column,
+            },
+        },
+    };
+
+    let error = error.renamed_rules(|rule| {
+        match rule {
+            Rule::EOI => "end of file",
+            Rule::Identifier => "identifier",
+            Rule::Import => "import",
+            Rule::Model => "model declaration",
+            Rule::Service => "service declaration",
+            Rule::ServiceMethod => "service method",
+            Rule::Field => "field",
+            Rule::FieldList => "field list",
+            Rule::UnionVariant => "union variant",
+            Rule::Shape => "shape",
+            Rule::Nullable => "`?`",
+            Rule::TypeParameters => "type parameters",
+            Rule::TypeArguments => "type arguments",
+            Rule::ObjectLiteral => "object literal",
+            Rule::StringLiteral => "string literal",
+            _ => return format!("{:?}", rule),
+        }
+        .to_owned()
+    });
+
+    Diagnostic::error(span, error.variant.message())
+}
+
+fn span_of(pair: &Pair<'_, Rule>) -> Span {
+    let (start_line, start_column) = pair.as_span().start_pos().line_col();
+    let (end_line, end_column) = pair.as_span().end_pos().line_col();
+
+    Span {
+        start: Position {
+            line: start_line,
+            column: start_column,
+        },
+        end: Position {
+            line: end_line,
+            column: end_column,
+        },
+    }
+}
+
+fn parse_import(pair: Pair<'_, Rule>) -> Result<Import<'_>> {
+    let mut pairs = pair.into_inner();
+
+    let first = pairs.next().unwrap();
+    let (names, path_pair) = match first.as_rule() {
+        Rule::ImportList => {
+            let names = first.into_inner().map(|pair| pair.as_str()).collect();
+            (Some(names), pairs.next().unwrap())
+        }
+        _ => (None, first),
+    };
+
+    let path = path_pair.as_str();
+
+    Ok(Import {
+        path: &path[1..path.len() - 1],
+        is_wildcard: names.is_none(),
+        names: names.unwrap_or_default(),
+    })
+}
+
+fn parse_model(pair: Pair<'_, Rule>) -> Result<(&str, Model<'_>)> {
+    Ok(match pair.as_rule() {
+        Rule::Struct => {
+            let (name, def) = parse_struct(pair)?;
+            (name, Model::Struct(def))
+        }
+        Rule::Enum => {
+            let (name, def) = parse_enum(pair)?;
+            (name, Model::Enum(def))
+        }
+        Rule::Union => {
+            let (name, def) = parse_union(pair)?;
+            (name, Model::Union(def))
+        }
+        Rule::Alias => {
+            let (name, def) = parse_alias(pair)?;
+            (name, Model::Alias(def))
+        }
+        Rule::External => {
+            let (name, def) = parse_external(pair)?;
+            (name, Model::External(def))
+        }
+        _ => unreachable!("unexpected model rule: {:?}", pair.as_rule()),
+    })
+}
+
+fn parse_service(pair: Pair<'_, Rule>) -> Result<(&str, Service<'_>)> {
+    let mut pairs = pair.into_inner();
+
+    let name = pairs.next().unwrap().as_str();
+
+    let methods = pairs
+        .map(parse_service_method)
+        .collect::<Result<OrderedHashMap<_, _>>>()?;
+
+    Ok((name, Service { methods }))
+}
+
+fn parse_service_method(pair: Pair<'_, Rule>) -> Result<(&str, Annotated<'_, ServiceMethod<'_>>)> {
+    let mut pairs = pair.into_inner();
+
+    let name = pairs.next().unwrap().as_str();
+
+    let inputs = pairs
+        .next()
+        .unwrap()
+        .into_inner()
+        .flat_map(|pair| {
+            pair.into_inner().map(|p| {
+                let mut pairs = p.into_inner();
+
+                let name = pairs.next().unwrap().as_str();
+                let shape = parse_annotated_shape(pairs.next().unwrap())?;
+                Ok((name, shape))
+            })
+        })
+        .collect::<Result<OrderedHashMap<_, _>>>()?;
+
+    let mut output = None;
+    let mut metadata = OrderedHashMap::new();
+
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::Shape => output = Some(parse_annotated_shape(pair)?),
+            Rule::ObjectLiteral => metadata = parse_metadata(pair)?,
+            _ => unreachable!("unexpected service method rule: {:?}", pair.as_rule()),
+        }
+    }
+
+    Ok((
+        name,
+        Annotated {
+            inner: ServiceMethod { inputs, output },
+            metadata,
+        },
+    ))
+}
+
+fn parse_fields(pair: Pair<'_, Rule>) -> Result<OrderedHashMap<&str, Annotated<'_, Shape<'_>>>> {
+    pair.into_inner()
+        .map(|pair| {
+            let mut pairs = pair.into_inner();
+            let name = pairs.next().unwrap().as_str();
+            let shape = parse_annotated_shape(pairs.next().unwrap())?;
+            Ok((name, shape))
+        })
+        .collect()
+}
+
+fn parse_struct(pair: Pair<'_, Rule>) -> Result<(&str, Struct<'_>)> {
+    let mut pairs = pair.into_inner();
+
+    let name = pairs.next().unwrap().as_str();
+    let mut type_parameters = Vec::new();
+    let mut fields = OrderedHashMap::new();
+
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::TypeParameters => type_parameters = parse_type_parameters(pair),
+            Rule::FieldList => fields = parse_fields(pair)?,
+            _ => unreachable!("unexpected struct rule: {:?}", pair.as_rule()),
+        }
+    }
+
+    Ok((
+        name,
+        Struct {
+            type_parameters,
+            fields,
+        },
+    ))
+}
+
+fn parse_enum(pair: Pair<'_, Rule>) -> Result<(&str, Enum<'_>)> {
+    let mut pairs = pair.into_inner();
+
+    let name = pairs.next().unwrap().as_str();
+    let fields = pairs.map(|pair| pair.as_str()).collect::<Vec<_>>();
+
+    Ok((name, Enum { fields }))
+}
+
+fn parse_union(pair: Pair<'_, Rule>) -> Result<(&str, Union<'_>)> {
+    let mut pairs = pair.into_inner();
+
+    let name = pairs.next().unwrap().as_str();
+    let mut variants = OrderedHashMap::new();
+    let mut metadata = OrderedHashMap::new();
+
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::UnionVariant => {
+                let (name, variant) = parse_union_variant(pair)?;
+                variants.insert(name, variant);
+            }
+            Rule::ObjectLiteral => metadata = parse_metadata(pair)?,
+            _ => unreachable!("unexpected union rule: {:?}", pair.as_rule()),
+        }
+    }
+
+    Ok((name, Union { variants, metadata }))
+}
+
+fn parse_union_variant(pair: Pair<'_, Rule>) -> Result<(&str, Variant<'_>)> {
+    let mut pairs = pair.into_inner();
+
+    let name = pairs.next().unwrap().as_str();
+    let variant = match pairs.next() {
+        None => Variant::Unit,
+        Some(pair) => match pair.as_rule() {
+            Rule::VariantShape => {
+                Variant::Tuple(parse_annotated_shape(pair.into_inner().next().unwrap())?)
+            }
+            Rule::VariantFields => Variant::Struct(
+                pair.into_inner()
+                    .next()
+                    .map(parse_fields)
+                    .transpose()?
+                    .unwrap_or_default(),
+            ),
+            _ => unreachable!("unexpected variant rule: {:?}", pair.as_rule()),
+        },
+    };
+
+    Ok((name, variant))
+}
+
+fn parse_alias(pair: Pair<'_, Rule>) -> Result<(&str, Alias<'_>)> {
+    let mut pairs = pair.into_inner();
+
+    let name = pairs.next().unwrap().as_str();
+    let mut next = pairs.next().unwrap();
+
+    let type_parameters = match next.as_rule() {
+        Rule::TypeParameters => {
+            let type_parameters = parse_type_parameters(next);
+            next = pairs.next().unwrap();
+            type_parameters
+        }
+        _ => Vec::new(),
+    };
+
+    let shape = parse_annotated_shape(next)?;
+
+    Ok((
+        name,
+        Alias {
+            type_parameters,
+            shape,
+        },
+    ))
+}
+
+fn parse_type_parameters(pair: Pair<'_, Rule>) -> Vec<&str> {
+    pair.into_inner().map(|pair| pair.as_str()).collect()
+}
+
+fn parse_external(pair: Pair<'_, Rule>) -> Result<(&str, External<'_>)> {
+    let mut pairs = pair.into_inner();
+
+    let name = pairs.next().unwrap().as_str();
+    let shape = parse_annotated_shape(pairs.next().unwrap())?;
+
+    Ok((name, External { shape }))
+}
+
+fn parse_annotated_shape(pair: Pair<'_, Rule>) -> Result<Annotated<'_, Shape<'_>>> {
+    let mut pairs = pair.into_inner();
+
+    let shape_pair = pairs.next().unwrap();
+    let shape = match shape_pair.as_rule() {
+        Rule::BoolShape => Shape::Bool,
+        Rule::Int8Shape => Shape::Int8,
+        Rule::Int16Shape => Shape::Int16,
+        Rule::Int32Shape => Shape::Int32,
+        Rule::Int64Shape => Shape::Int64,
+        Rule::Float32Shape => Shape::Float32,
+        Rule::Float64Shape => Shape::Float64,
+        Rule::StringShape => Shape::String,
+        Rule::ListShape => {
+            let mut pairs = shape_pair.into_inner();
+            let shape = parse_annotated_shape(pairs.next().unwrap())?;
+            Shape::List(Box::new(shape))
+        }
+        Rule::MapShape => {
+            let mut pairs = shape_pair.into_inner();
+            let key_shape = parse_annotated_shape(pairs.next().unwrap())?;
+            let value_shape = parse_annotated_shape(pairs.next().unwrap())?;
+            Shape::Map(Box::new(key_shape), Box::new(value_shape))
+        }
+        Rule::ReferenceShape => {
+            let mut pairs = shape_pair.into_inner();
+            let name = pairs.next().unwrap().as_str();
+            let arguments = pairs
+                .next()
+                .map(|pair| pair.into_inner().map(parse_annotated_shape).collect())
+                .transpose()?
+                .unwrap_or_default();
+            Shape::Reference(name, arguments)
+        }
+        _ => unreachable!("unexpected shape rule: {:?}", shape_pair.as_rule()),
+    };
+
+    let mut is_nullable = false;
+    let mut metadata = OrderedHashMap::new();
+
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::Nullable => {
+                is_nullable = true;
+            }
+            Rule::ObjectLiteral => {
+                metadata = parse_metadata(pair)?;
+            }
+            _ => unreachable!("unexpected shape rule: {:?}", pair.as_rule()),
+        }
+    }
+
+    Ok(Annotated {
+        inner: if is_nullable {
+            Shape::Nullable(Box::new(shape))
+        } else {
+            shape
+        },
+        metadata,
+    })
+}
+
+fn parse_metadata(pair: Pair<'_, Rule>) -> Result<OrderedHashMap<&str, Literal<'_>>> {
+    match parse_literal(pair)? {
+        Literal::Object(fields) => Ok(fields),
+        _ => unreachable!("unexpected metadata literal"),
+    }
+}
+
+fn parse_literal(pair: Pair<'_, Rule>) -> Result<Literal<'_>> {
+    Ok(match pair.as_rule() {
+        Rule::BoolLiteral => Literal::Bool(pair.as_str() == "true"),
+        Rule::IntLiteral => Literal::Int(pair.as_str().parse().map_err(|_| {
+            Diagnostic::error(span_of(&pair), "integer literal is out of range")
+        })?),
+        Rule::FloatLiteral => Literal::Float(
+            pair.as_str()
+                .parse()
+                .map_err(|_| Diagnostic::error(span_of(&pair), "invalid float literal"))?,
+        ),
+        Rule::CharLiteral => Literal::Char(pair.as_str()[1..].chars().next().unwrap()),
+        Rule::StringLiteral => Literal::String(&pair.as_str()[1..pair.as_str().len() - 1]),
+        Rule::ObjectLiteral => {
+            let mut pairs = pair.into_inner();
+            let mut fields = OrderedHashMap::new();
+
+            while let Some(pair) = pairs.next() {
+                let value = parse_literal(pairs.next().unwrap())?;
+                fields.insert(pair.as_str(), value);
+            }
+
+            Literal::Object(fields)
+        }
+        Rule::ArrayLiteral => {
+            let values = pair
+                .into_inner()
+                .map(parse_literal)
+                .collect::<Result<_>>()?;
+            Literal::Array(values)
+        }
+        rule => {
+            return Err(Diagnostic::error(
+                span_of(&pair),
+                format!("unexpected literal rule: {:?}", rule),
+            ))
+        }
+    })
+}
+
+/// Makes sure every reference to a generic model declared in this schema
+/// passes the expected number of type arguments. References to imported
+/// models are left to the checker.
+fn check_arity(source: &str, schema: &Schema) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut check = |scope: &[&str], shape: &Shape| {
+        check_shape_arity(source, schema, scope, shape, &mut diagnostics)
+    };
+
+    for (_, model) in schema.models.iter() {
+        let scope = model.type_parameters();
+        match model {
+            Model::Struct(inner) => inner
+                .fields
+                .iter()
+                .for_each(|(_, shape)| check(scope, &shape.inner)),
+            Model::Union(inner) => inner.variants.iter().for_each(|(_, variant)| match variant {
+                Variant::Unit => {}
+                Variant::Tuple(shape) => check(scope, shape),
+                Variant::Struct(fields) => fields
+                    .iter()
+                    .for_each(|(_, shape)| check(scope, &shape.inner)),
+            }),
+            Model::Alias(inner) => check(scope, &inner.shape.inner),
+            Model::External(inner) => check(scope, &inner.shape.inner),
+            Model::Enum(_) => {}
+        }
+    }
+
+    for (_, service) in schema.services.iter() {
+        for (_, method) in service.methods.iter() {
+            method
+                .inner
+                .inputs
+                .iter()
+                .for_each(|(_, shape)| check(&[], shape));
+            if let Some(ref shape) = method.inner.output {
+                check(&[], shape);
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn check_shape_arity(
+    source: &str,
+    schema: &Schema,
+    scope: &[&str],
+    shape: &Shape,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    match shape {
+        Shape::Nullable(inner) => check_shape_arity(source, schema, scope, inner, diagnostics),
+        Shape::List(inner) => check_shape_arity(source, schema, scope, inner, diagnostics),
+        Shape::Map(key, value) => {
+            check_shape_arity(source, schema, scope, key, diagnostics);
+            check_shape_arity(source, schema, scope, value, diagnostics);
+        }
+        Shape::Reference(name, arguments) => {
+            let expected = if scope.contains(name) {
+                Some(0)
+            } else {
+                schema
+                    .models
+                    .get(name)
+                    .map(|model| model.type_parameters().len())
+            };
+
+            if let Some(expected) = expected {
+                if expected != arguments.len() {
+                    diagnostics.push(Diagnostic::error(
+                        Span::of(source, name),
+                        format!(
+                            "{} expects {} type argument(s), but {} were given",
+                            name,
+                            expected,
+                            arguments.len()
+                        ),
+                    ));
+                }
+            }
+
+            arguments.iter().for_each(|argument| {
+                check_shape_arity(source, schema, scope, argument, diagnostics)
+            });
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(literals: &str) -> OrderedHashMap<&str, Literal<'_>> {
+        let schema = parse_schema(literals).unwrap();
+        let Some(Model::Struct(inner)) = schema.models.get("Literals") else {
+            panic!("expected the struct `Literals`");
+        };
+        inner.fields.get("field").unwrap().metadata.clone()
+    }
+
+    #[test]
+    fn parses_literals() {
+        let source = "struct Literals {
+          field: String & {
+            bool: true,
+            int: 42,
+            float: 1.5,
+            string: \"http://example.com\",
+            char: 'x',
+            unicode: 'ä',
+            array: [1, 2,],
+            object: { nested: false },
+            empty: {},
+          },
+        }";
+
+        let mut object = OrderedHashMap::new();
+        object.insert("nested", Literal::Bool(false));
+
+        let mut expected = OrderedHashMap::new();
+        expected.insert("bool", Literal::Bool(true));
+        expected.insert("int", Literal::Int(42));
+        expected.insert("float", Literal::Float(1.5));
+        expected.insert("string", Literal::String("http://example.com"));
+        expected.insert("char", Literal::Char('x'));
+        expected.insert("unicode", Literal::Char('ä'));
+        expected.insert(
+            "array",
+            Literal::Array(vec![Literal::Int(1), Literal::Int(2)]),
+        );
+        expected.insert("object", Literal::Object(object));
+        expected.insert("empty", Literal::Object(OrderedHashMap::new()));
+
+        assert_eq!(metadata(source), expected);
+    }
+
+    #[test]
+    fn reports_out_of_range_integers() {
+        let source = "struct Literals {\n  field: Int64 & { max: 99999999999999999999 },\n}\n";
+        let diagnostics = parse_schema(source).unwrap_err();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "integer literal is out of range");
+    }
+
+    #[test]
+    fn formats_literals_as_written() {
+        let source = "struct Literals {
+          field: String & { char: 'x', float: 100000000000000000000.0, small: 0.25 },
+        }";
+
+        let literals = metadata(source)
+            .iter()
+            .map(|(_, literal)| literal.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(literals, ["'x'", "100000000000000000000.0", "0.25"]);
+    }
+
+    #[test]
+    fn parses_services_with_a_single_method() {
+        let source = "service ContactService {\n  send(message: String),\n}\n";
+        let schema = parse_schema(source).unwrap();
+        let methods = &schema.services.get("ContactService").unwrap().methods;
+        assert_eq!(methods.iter().count(), 1);
+    }
+}
@@ -0,0 +1,266 @@
+use super::{diagnostic::Diagnostic, map::OrderedHashMap, parser::parse_schema};
+use std::{fmt::Display, ops::Deref};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema<'a> {
+    pub imports: Vec<Import<'a>>,
+    pub models: OrderedHashMap<&'a str, Model<'a>>,
+    pub services: OrderedHashMap<&'a str, Service<'a>>,
+}
+
+impl<'a> Schema<'a> {
+    pub fn parse(source: &'a str) -> Result<Self, Vec<Diagnostic>> {
+        parse_schema(source)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import<'a> {
+    /// Path of the imported schema, relative to the importing file
+    pub path: &'a str,
+    /// Imported model names, filled in by the loader for wildcard imports
+    pub names: Vec<&'a str>,
+    pub is_wildcard: bool,
+}
+
+impl<'a> Import<'a> {
+    /// Returns the import path without its `.hgen` extension
+    pub fn module_path(&self) -> &'a str {
+        self.path.strip_suffix(".hgen").unwrap_or(self.path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Model<'a> {
+    Struct(Struct<'a>),
+    Enum(Enum<'a>),
+    Union(Union<'a>),
+    Alias(Alias<'a>),
+    External(External<'a>),
+}
+
+impl<'a> Model<'a> {
+    pub fn type_parameters(&self) -> &[&'a str] {
+        match self {
+            Model::Struct(inner) => &inner.type_parameters,
+            Model::Alias(inner) => &inner.type_parameters,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Service<'a> {
+    pub methods: OrderedHashMap<&'a str, Annotated<'a, ServiceMethod<'a>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceMethod<'a> {
+    pub inputs: OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>,
+    pub output: Option<Annotated<'a, Shape<'a>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alias<'a> {
+    pub type_parameters: Vec<&'a str>,
+    pub shape: Annotated<'a, Shape<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct External<'a> {
+    pub shape: Annotated<'a, Shape<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Struct<'a> {
+    pub type_parameters: Vec<&'a str>,
+    pub fields: OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum<'a> {
+    pub fields: Vec<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Union<'a> {
+    pub variants: OrderedHashMap<&'a str, Variant<'a>>,
+    pub metadata: OrderedHashMap<&'a str, Literal<'a>>,
+}
+
+impl<'a> Union<'a> {
+    /// Name of the property holding the variant name on the wire, `type` by default
+    pub fn tag(&self) -> &'a str {
+        self.metadata_string("tag").unwrap_or("type")
+    }
+
+    /// Name of the property holding the variant payload on the wire, `value` by default
+    pub fn content(&self) -> &'a str {
+        self.metadata_string("content").unwrap_or("value")
+    }
+
+    fn metadata_string(&self, key: &str) -> Option<&'a str> {
+        match self.metadata.get(key) {
+            Some(Literal::String(value)) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Variant<'a> {
+    Unit,
+    Tuple(Annotated<'a, Shape<'a>>),
+    Struct(OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape<'a> {
+    Bool,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    String,
+    List(Box<Annotated<'a, Shape<'a>>>),
+    Map(Box<Annotated<'a, Shape<'a>>>, Box<Annotated<'a, Shape<'a>>>),
+    /// A named model or type parameter, along with its type arguments
+    Reference(&'a str, Vec<Annotated<'a, Shape<'a>>>),
+    Nullable(Box<Shape<'a>>),
+}
+
+impl<'a> Display for Shape<'a> {
+    /// Formats the shape the way it is written in a schema
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shape::Bool => write!(f, "Bool"),
+            Shape::Int8 => write!(f, "Int8"),
+            Shape::Int16 => write!(f, "Int16"),
+            Shape::Int32 => write!(f, "Int32"),
+            Shape::Int64 => write!(f, "Int64"),
+            Shape::Float32 => write!(f, "Float32"),
+            Shape::Float64 => write!(f, "Float64"),
+            Shape::String => write!(f, "String"),
+            Shape::List(inner) => write!(f, "List<{}>", inner),
+            Shape::Map(key, value) => write!(f, "Map<{}, {}>", key, value),
+            Shape::Reference(name, arguments) if arguments.is_empty() => write!(f, "{}", name),
+            Shape::Reference(name, arguments) => {
+                let arguments = arguments.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+                write!(f, "{}<{}>", name, arguments.join(", "))
+            }
+            Shape::Nullable(inner) => write!(f, "{}?", inner),
+        }
+    }
+}
+
+/// Something followed by metadata in a schema, e.g. `String & { max: 10 }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotated<'a, T> {
+    pub metadata: OrderedHashMap<&'a str, Literal<'a>>,
+    pub inner: T,
+}
+
+impl<'a, T> Annotated<'a, T> {
+    /// Wraps `inner` without any metadata
+    pub fn new(inner: T) -> Self {
+        Annotated {
+            metadata: OrderedHashMap::new(),
+            inner,
+        }
+    }
+
+    /// Maps the annotated value, keeping its metadata
+    pub fn map<U>(&self, f: impl FnOnce(&T) -> U) -> Annotated<'a, U> {
+        Annotated {
+            metadata: self.metadata.clone(),
+            inner: f(&self.inner),
+        }
+    }
+}
+
+impl<T> Deref for Annotated<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Display> Display for Annotated<'_, T> {
+    /// Formats the annotated value along with its metadata, if any
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.metadata.is_empty() {
+            write!(f, "{}", self.inner)
+        } else {
+            write!(
+                f,
+                "{} & {}",
+                self.inner,
+                Literal::Object(self.metadata.clone())
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal<'a> {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Char(char),
+    String(&'a str),
+    Object(OrderedHashMap<&'a str, Literal<'a>>),
+    Array(Vec<Literal<'a>>),
+}
+
+impl<'a> Display for Literal<'a> {
+    /// Formats the literal the way it is written in a schema
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Bool(value) => write!(f, "{}", value),
+            Literal::Int(value) => write!(f, "{}", value),
+            // floats are always written with a fraction, and never with an exponent
+            Literal::Float(value) if value.fract() == 0.0 => write!(f, "{:.1}", value),
+            Literal::Float(value) => write!(f, "{}", value),
+            Literal::Char(value) => write!(f, "'{}'", value),
+            Literal::String(value) => write!(f, "\"{}\"", value),
+            Literal::Array(values) => {
+                let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                write!(f, "[{}]", values.join(", "))
+            }
+            Literal::Object(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Literal::Object(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect::<Vec<_>>();
+                write!(f, "{{ {} }}", fields.join(", "))
+            }
+        }
+    }
+}
+
+/// Resolves backslash escapes within the raw contents of a string literal
+pub fn unescape(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => output.push('\n'),
+            Some('r') => output.push('\r'),
+            Some('t') => output.push('\t'),
+            Some(other) => output.push(other),
+            None => output.push('\\'),
+        }
+    }
+
+    output
+}
@@ -0,0 +1,7 @@
+//! Parser, checker and code generators of the hGEN schema language, shared by
+//! the command line tool, build scripts and the `include_schema!` macro.
+
+pub mod emit;
+pub mod lang;
+
+pub use lang::{checker, schema::Schema};
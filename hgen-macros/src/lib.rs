@@ -0,0 +1,73 @@
+//! Procedural macros of the hGEN schema language, re-exported by `hgen`.
+
+use hgen_core::{emit, lang::loader::Sources};
+use proc_macro::TokenStream;
+use std::{env, path::PathBuf};
+use syn::{parse::Nothing, parse_macro_input, Error, LitStr};
+
+/// Expands to the Rust code generated for a schema, given its path relative to
+/// the root of the calling package. Imported schemas are referred to as
+/// sibling modules, the same way as in generated files.
+///
+/// ```ignore
+/// mod api {
+///     hgen::include_schema!("api.hgen");
+/// }
+/// ```
+#[proc_macro]
+pub fn include_schema(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+
+    match include(&path) {
+        Ok(output) => output,
+        Err(error) => error.into_compile_error().into(),
+    }
+}
+
+/// Expands to the runtime shared by the services of included schemas, which
+/// refer to it as the sibling module `rpc`.
+///
+/// ```ignore
+/// mod rpc {
+///     hgen::include_runtime!();
+/// }
+/// ```
+#[proc_macro]
+pub fn include_runtime(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as Nothing);
+
+    emit::rs::emit_runtime().parse().unwrap()
+}
+
+fn include(path: &LitStr) -> Result<TokenStream, Error> {
+    let root = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let input = PathBuf::from(root).join(path.value());
+
+    let sources =
+        Sources::load(&input).map_err(|error| Error::new(path.span(), format!("{:#}", error)))?;
+
+    let modules = sources.compile().map_err(|diagnostics| {
+        diagnostics
+            .iter()
+            .map(|diagnostic| Error::new(path.span(), diagnostic))
+            .reduce(|mut errors, error| {
+                errors.combine(error);
+                errors
+            })
+            .unwrap()
+    })?;
+
+    let mut output = String::new();
+
+    // including the schemas makes the compiler expand the macro again once they change
+    for file in sources.files.iter() {
+        output.push_str(&format!("const _: &str = include_str!({:?});\n", file.path));
+    }
+
+    let name = input.file_stem().unwrap().to_str().unwrap();
+    output.push_str(&emit::rs::emit_schema(name, &modules[0].schema, &emit::rpc::path(0)));
+
+    output.parse().map_err(|error| {
+        Error::new(path.span(), format!("failed to expand schema: {}", error))
+    })
+}
@@ -0,0 +1,159 @@
+//! Helpers for generating code from Cargo build scripts.
+//!
+//! ```no_run
+//! // build.rs
+//! fn main() -> anyhow::Result<()> {
+//!     let output = std::path::Path::new(&std::env::var("OUT_DIR")?).join("api.rs");
+//!     hgen::build::compile("api.hgen").rust_out(output)
+//! }
+//! ```
+//!
+//! The generated file can then be included with
+//! `include!(concat!(env!("OUT_DIR"), "/api.rs"));`. Schemas with imports are
+//! emitted along with every schema they import, each within a module named
+//! after its file, and the items of the entry schema are re-exported. Their
+//! externals are taken from the module the file is included in. Schemas with
+//! services are emitted the same way, along with their runtime in the module
+//! `rpc`.
+
+use crate::{
+    emit,
+    lang::{
+        loader::{Module, Sources},
+        schema::{Model, Schema},
+    },
+};
+use anyhow::bail;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Compiles the schema at `input`, relative to the package being built
+pub fn compile(input: impl AsRef<Path>) -> Compile {
+    Compile {
+        input: input.as_ref().to_path_buf(),
+    }
+}
+
+pub struct Compile {
+    input: PathBuf,
+}
+
+impl Compile {
+    /// Writes Rust code for the schema to `output`, telling Cargo to rerun the
+    /// build script whenever the schema or one of its imports changes
+    pub fn rust_out(&self, output: impl AsRef<Path>) -> anyhow::Result<()> {
+        self.emit(output.as_ref(), emit::rs::emit_schema)
+    }
+
+    fn emit(&self, output: &Path, emit: fn(&str, &Schema, &str) -> String) -> anyhow::Result<()> {
+        // the entry is reported before loading, so a schema which fails to load
+        // is retried once fixed, and its imports are known only after loading
+        println!("cargo:rerun-if-changed={}", self.input.display());
+
+        let sources = Sources::load(&self.input)?;
+        for file in sources.files.iter().skip(1) {
+            println!("cargo:rerun-if-changed={}", file.path.display());
+        }
+
+        let modules = match sources.compile() {
+            Ok(modules) => modules,
+            Err(diagnostics) => bail!(
+                "failed to compile {}\n\n{}",
+                self.input.display(),
+                sources.render(&diagnostics)
+            ),
+        };
+
+        let name = self.input.file_stem().unwrap().to_str().unwrap();
+        let has_services = modules.iter().any(|module| !module.schema.services.is_empty());
+        let content = match modules.len() {
+            1 if !has_services => emit(name, &modules[0].schema, &emit::rpc::path(0)),
+            _ => emit_modules(&modules, emit)?,
+        };
+
+        // unchanged files are left alone to avoid needless recompilation
+        if fs::read_to_string(output).ok().as_deref() != Some(content.as_str()) {
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(output, content)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Emits every module within nested modules mirroring the directories of the
+/// schemas, so imports referring to sibling modules resolve
+fn emit_modules(
+    modules: &[Module],
+    emit: fn(&str, &Schema, &str) -> String,
+) -> anyhow::Result<String> {
+    let root = modules[0].path.parent().unwrap();
+
+    let mut files = Vec::new();
+    for module in modules {
+        let Ok(path) = module.path.strip_prefix(root) else {
+            bail!(
+                "{} is imported from outside of {}, which build scripts do not support",
+                module.path.display(),
+                root.display()
+            );
+        };
+        let segments = path
+            .with_extension("")
+            .iter()
+            .map(|segment| segment.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        let runtime = emit::rpc::path(segments.len() - 1);
+        let mut content = emit(segments.last().unwrap(), &module.schema, &runtime);
+
+        // externals are implemented next to the included file
+        if module.schema.models.iter().any(|(_, model)| matches!(model, Model::External(_))) {
+            content.insert_str(0, &format!("use {}external;\n", "super::".repeat(segments.len())));
+        }
+
+        files.push((segments, content));
+    }
+
+    // the runtime of services is shared by all modules
+    if modules.iter().any(|module| !module.schema.services.is_empty()) {
+        files.push((vec![emit::rpc::MODULE.to_owned()], emit::rs::emit_runtime()));
+    }
+
+    let mut output = nest(&files);
+    output.push_str(&format!("pub use {}::*;\n", files[0].0[0]));
+
+    Ok(output)
+}
+
+fn nest(files: &[(Vec<String>, String)]) -> String {
+    let mut output = String::new();
+    let mut directories: Vec<&str> = Vec::new();
+
+    for (segments, content) in files {
+        match segments.as_slice() {
+            [name] => {
+                output.push_str(&format!("pub mod {} {{\n", name));
+                output.push_str(content);
+                output.push_str("}\n");
+            }
+            [directory, ..] if !directories.contains(&directory.as_str()) => {
+                directories.push(directory);
+                let children = files
+                    .iter()
+                    .filter(|(segments, _)| segments.len() > 1 && segments[0] == *directory)
+                    .map(|(segments, content)| (segments[1..].to_vec(), content.clone()))
+                    .collect::<Vec<_>>();
+                output.push_str(&format!("pub mod {} {{\n", directory));
+                output.push_str(&nest(&children));
+                output.push_str("}\n");
+            }
+            _ => {}
+        }
+    }
+
+    output
+}
@@ -0,0 +1,82 @@
+//! Project configuration read by `hgen build`, describing which schemas to
+//! compile and which targets to emit for each of them.
+//!
+//! ```toml
+//! inputs = ["api/todo.hgen"]
+//!
+//! [[targets]]
+//! language = "typescript"
+//! output = "web/src/api"
+//!
+//! [[targets]]
+//! plugin = "hgen-gen-kotlin"
+//! output = "android/src/api"
+//! options = { package = "com.example.api" }
+//! ```
+
+use crate::Strategy;
+use anyhow::{anyhow, bail, Context};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const FILE_NAME: &str = "hgen.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub inputs: Vec<PathBuf>,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    /// One of the built-in languages
+    pub language: Option<String>,
+    /// Name or path of an external generator, used instead of a language
+    pub plugin: Option<String>,
+    /// Directory to emit the files of every input into
+    pub output: PathBuf,
+    /// Options passed on to the emitter
+    #[serde(default)]
+    pub options: Map<String, Value>,
+}
+
+impl Config {
+    /// Reads a configuration, resolving all paths relative to its directory
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut config: Config = toml::from_str(&source)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+
+        let root = path.parent().unwrap_or(Path::new(""));
+        for input in config.inputs.iter_mut() {
+            *input = root.join(&*input);
+        }
+        for target in config.targets.iter_mut() {
+            target.output = root.join(&target.output);
+        }
+
+        Ok(config)
+    }
+}
+
+impl Target {
+    pub fn strategy(&self) -> anyhow::Result<Strategy> {
+        match (&self.language, &self.plugin) {
+            (Some(language), None) => Strategy::from_language(language)
+                .ok_or_else(|| anyhow!("unsupported language `{}`", language)),
+            (None, Some(plugin)) => Ok(Strategy::Plugin(plugin.to_owned())),
+            _ => bail!(
+                "target {} needs either a language or a plugin",
+                self.output.display()
+            ),
+        }
+    }
+}
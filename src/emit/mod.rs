@@ -1,3 +0,0 @@
-pub mod dart;
-pub mod rs;
-pub mod ts;
@@ -1,3 +0,0 @@
-pub mod map;
-pub mod parser;
-pub mod schema;
@@ -0,0 +1,8 @@
+//! Parser, checker and code generators of the hGEN schema language, for use
+//! in build scripts and other tools. See [`build`] for generating code during
+//! `cargo build`, or [`include_schema!`] for generating it in place.
+
+pub mod build;
+
+pub use hgen_core::{checker, emit, lang, Schema};
+pub use hgen_macros::{include_runtime, include_schema};
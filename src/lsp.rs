@@ -0,0 +1,750 @@
+//! Language server for `.hgen` files, speaking LSP over stdio. Every request
+//! analyzes the whole workspace from scratch, using the contents of open
+//! documents instead of the files on disk.
+
+use hgen::lang::{
+    checker,
+    diagnostic::{self, Diagnostic},
+    format::format_model,
+    loader::{find_schemas, Module, Sources},
+    map::OrderedHashMap,
+    schema::*,
+};
+use anyhow::{anyhow, bail};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, References, Rename, Request as _},
+    CompletionItem, CompletionItemKind, CompletionParams, CompletionResponse,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, InitializeParams, Location, MarkupContent, MarkupKind, OneOf,
+    Position, PublishDiagnosticsParams, Range, ReferenceParams, RenameParams, ServerCapabilities,
+    TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+    WorkspaceEdit,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+const PRIMITIVES: [&str; 10] = [
+    "Bool", "Int8", "Int16", "Int32", "Int64", "Float32", "Float64", "String", "List", "Map",
+];
+
+pub fn run() -> anyhow::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(Default::default()),
+        rename_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    let params = connection.initialize(serde_json::to_value(capabilities)?)?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+
+    let mut server = Server::new(&params);
+    server.main_loop(&connection)?;
+
+    drop(connection);
+    io_threads.join()?;
+
+    Ok(())
+}
+
+struct Server {
+    /// Directories searched for schemas, so references can be found in files
+    /// which are not open
+    roots: Vec<PathBuf>,
+    /// Contents of all open documents
+    documents: HashMap<PathBuf, String>,
+    /// Contents of open documents when they last parsed, used for completion
+    /// while the document is being edited
+    parsed: HashMap<PathBuf, String>,
+    /// Files which diagnostics have been published for
+    published: HashSet<PathBuf>,
+}
+
+impl Server {
+    #[allow(deprecated)]
+    fn new(params: &InitializeParams) -> Self {
+        let mut roots = params
+            .workspace_folders
+            .iter()
+            .flatten()
+            .filter_map(|folder| folder.uri.to_file_path().ok())
+            .collect::<Vec<_>>();
+
+        if roots.is_empty() {
+            roots.extend(
+                params
+                    .root_uri
+                    .as_ref()
+                    .and_then(|uri| uri.to_file_path().ok()),
+            );
+        }
+
+        Self {
+            roots,
+            documents: HashMap::new(),
+            parsed: HashMap::new(),
+            published: HashSet::new(),
+        }
+    }
+
+    fn main_loop(&mut self, connection: &Connection) -> anyhow::Result<()> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    if self.handle_notification(notification) {
+                        for notification in self.publish_diagnostics() {
+                            connection
+                                .sender
+                                .send(Message::Notification(notification))?;
+                        }
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            HoverRequest::METHOD => handle(request, |params| self.hover(params)),
+            GotoDefinition::METHOD => handle(request, |params| self.definition(params)),
+            References::METHOD => handle(request, |params| self.references(params)),
+            Completion::METHOD => handle(request, |params| self.completion(params)),
+            Rename::METHOD => handle(request, |params| self.rename(params)),
+            method => {
+                return Response::new_err(
+                    id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unsupported request {}", method),
+                )
+            }
+        };
+
+        match result {
+            Ok(result) => Response::new_ok(id, result),
+            Err(error) => Response::new_err(id, ErrorCode::RequestFailed as i32, error.to_string()),
+        }
+    }
+
+    /// Updates the open documents, returning whether anything changed
+    fn handle_notification(&mut self, notification: Notification) -> bool {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Ok(params) =
+                    serde_json::from_value::<DidOpenTextDocumentParams>(notification.params)
+                else {
+                    return false;
+                };
+                self.update(&params.text_document.uri, params.text_document.text);
+            }
+            DidChangeTextDocument::METHOD => {
+                let Ok(params) =
+                    serde_json::from_value::<DidChangeTextDocumentParams>(notification.params)
+                else {
+                    return false;
+                };
+                // documents are always synced in full
+                let Some(change) = params.content_changes.into_iter().last() else {
+                    return false;
+                };
+                self.update(&params.text_document.uri, change.text);
+            }
+            DidCloseTextDocument::METHOD => {
+                let Ok(params) =
+                    serde_json::from_value::<DidCloseTextDocumentParams>(notification.params)
+                else {
+                    return false;
+                };
+                let path = to_path(&params.text_document.uri);
+                self.documents.remove(&path);
+                self.parsed.remove(&path);
+            }
+            DidSaveTextDocument::METHOD => {}
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn update(&mut self, uri: &Url, text: String) {
+        let path = to_path(uri);
+        if Schema::parse(&text).is_ok() {
+            self.parsed.insert(path.clone(), text.clone());
+        }
+        self.documents.insert(path, text);
+    }
+
+    /// Loads every schema within the workspace and all open documents,
+    /// optionally replacing the contents of a single file
+    fn analyze(&self, replace: Option<(&Path, &str)>) -> anyhow::Result<Sources> {
+        let mut entries = Vec::new();
+        for root in self.roots.iter() {
+            find_schemas(root, &mut entries);
+        }
+        entries.extend(self.documents.keys().cloned());
+
+        Sources::load_all(entries, |path| match replace {
+            Some((replaced, source)) if replaced == path => Ok(source.to_owned()),
+            _ => match self.documents.get(path) {
+                Some(source) => Ok(source.clone()),
+                None => fs::read_to_string(path),
+            },
+        })
+    }
+
+    fn publish_diagnostics(&mut self) -> Vec<Notification> {
+        let Ok(sources) = self.analyze(None) else {
+            return Vec::new();
+        };
+
+        let (modules, mut diagnostics) = sources.parse_all();
+        diagnostics.extend(checker::check(&modules).into_iter().filter(|diagnostic| {
+            modules
+                .iter()
+                .any(|module| module.is_complete && diagnostic.file.as_deref() == Some(module.path))
+        }));
+
+        let mut by_file: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+        for file in sources.files.iter().map(|file| &file.path).chain(&self.published) {
+            by_file.entry(file.clone()).or_default();
+        }
+        for diagnostic in diagnostics {
+            if let Some(file) = diagnostic.file.clone() {
+                by_file.entry(file).or_default().push(diagnostic);
+            }
+        }
+
+        let mut notifications = Vec::new();
+        self.published.clear();
+
+        for (path, diagnostics) in by_file {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let source = sources
+                .get(&path)
+                .map(|file| file.source.as_str())
+                .unwrap_or_default();
+
+            if !diagnostics.is_empty() {
+                self.published.insert(path);
+            }
+
+            let params = PublishDiagnosticsParams {
+                uri,
+                version: None,
+                diagnostics: diagnostics
+                    .into_iter()
+                    .map(|diagnostic| lsp_types::Diagnostic {
+                        range: Range::new(
+                            from_diagnostic(source, diagnostic.span.start),
+                            from_diagnostic(source, diagnostic.span.end),
+                        ),
+                        severity: Some(match diagnostic.severity {
+                            diagnostic::Severity::Error => lsp_types::DiagnosticSeverity::ERROR,
+                        }),
+                        source: Some("hgen".to_owned()),
+                        message: diagnostic.message,
+                        ..Default::default()
+                    })
+                    .collect(),
+            };
+            notifications.push(Notification::new(PublishDiagnostics::METHOD.to_owned(), params));
+        }
+
+        notifications
+    }
+
+    fn hover(&self, params: HoverParams) -> anyhow::Result<Option<Hover>> {
+        let sources = self.analyze(None)?;
+        let (modules, _) = sources.parse_all();
+        let Some(occurrence) = find_occurrence(&modules, &params.text_document_position_params)
+        else {
+            return Ok(None);
+        };
+
+        let target = occurrence.target;
+        let module = &modules[target.module];
+        let name = &module.source[target.start..target.end];
+
+        let contents = match find_model(module, target.start) {
+            Some(model) => {
+                let mut contents = format!("```hgen\n{}\n```", format_model(name, model));
+                let shape = match model {
+                    Model::Alias(inner) => Some(&inner.shape.inner),
+                    Model::External(inner) => Some(&inner.shape.inner),
+                    _ => None,
+                };
+                if let Some(shape) = shape {
+                    let resolved = resolve_shape(&modules, target.module, shape, 0);
+                    if resolved != *shape {
+                        contents.push_str(&format!("\n\nresolves to `{}`", resolved));
+                    }
+                }
+                if target.module != occurrence.at.module {
+                    let file = module.path.file_name().unwrap_or_default();
+                    contents.push_str(&format!("\n\ndeclared in `{}`", file.to_string_lossy()));
+                }
+                contents
+            }
+            None => format!("```hgen\n{}\n```\n\ntype parameter", name),
+        };
+
+        Ok(Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: contents,
+            }),
+            range: Some(to_range(&modules, occurrence.at)),
+        }))
+    }
+
+    fn definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> anyhow::Result<Option<GotoDefinitionResponse>> {
+        let sources = self.analyze(None)?;
+        let (modules, _) = sources.parse_all();
+        let Some(occurrence) = find_occurrence(&modules, &params.text_document_position_params)
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(GotoDefinitionResponse::Scalar(to_location(
+            &modules,
+            occurrence.target,
+        )?)))
+    }
+
+    fn references(&self, params: ReferenceParams) -> anyhow::Result<Option<Vec<Location>>> {
+        let sources = self.analyze(None)?;
+        let (modules, _) = sources.parse_all();
+        let Some(occurrence) = find_occurrence(&modules, &params.text_document_position) else {
+            return Ok(None);
+        };
+
+        let locations = collect_occurrences(&modules)
+            .into_iter()
+            .filter(|other| other.target == occurrence.target)
+            .filter(|other| params.context.include_declaration || other.at != other.target)
+            .map(|other| to_location(&modules, other.at))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Some(locations))
+    }
+
+    fn completion(&self, params: CompletionParams) -> anyhow::Result<Option<CompletionResponse>> {
+        let path = to_path(&params.text_document_position.text_document.uri);
+
+        let mut items = PRIMITIVES
+            .iter()
+            .map(|name| CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        // the document is most likely incomplete while typing, so fall back to
+        // the models of its last version which did parse
+        let replace = match self.documents.get(&path) {
+            Some(source) if Schema::parse(source).is_ok() => None,
+            _ => self
+                .parsed
+                .get(&path)
+                .map(|source| (path.as_path(), source.as_str())),
+        };
+
+        let sources = self.analyze(replace)?;
+        let (modules, _) = sources.parse_all();
+        let Some(index) = modules.iter().position(|module| module.path == path) else {
+            return Ok(Some(CompletionResponse::Array(items)));
+        };
+        let module = &modules[index];
+
+        let imported = module
+            .schema
+            .imports
+            .iter()
+            .zip(&module.dependencies)
+            .flat_map(|(import, dependency)| {
+                import.names.iter().filter_map(|name| {
+                    let model = modules[*dependency].schema.models.get(name)?;
+                    Some((*name, model))
+                })
+            });
+
+        for (name, model) in module
+            .schema
+            .models
+            .iter()
+            .map(|(name, model)| (*name, model))
+            .chain(imported)
+        {
+            items.push(CompletionItem {
+                label: name.to_owned(),
+                kind: Some(match model {
+                    Model::Struct(_) => CompletionItemKind::STRUCT,
+                    Model::Enum(_) | Model::Union(_) => CompletionItemKind::ENUM,
+                    Model::Alias(_) | Model::External(_) => CompletionItemKind::CLASS,
+                }),
+                detail: Some(format_model(name, model)),
+                ..Default::default()
+            });
+        }
+
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    fn rename(&self, params: RenameParams) -> anyhow::Result<Option<WorkspaceEdit>> {
+        let name = params.new_name.as_str();
+        let mut chars = name.chars();
+        if !chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            || !chars.all(|c| c.is_ascii_alphanumeric())
+        {
+            bail!("`{}` is not a valid identifier", name);
+        }
+        if PRIMITIVES.contains(&name) {
+            bail!("`{}` is a primitive type", name);
+        }
+
+        let sources = self.analyze(None)?;
+        let (modules, _) = sources.parse_all();
+        let Some(occurrence) = find_occurrence(&modules, &params.text_document_position) else {
+            return Ok(None);
+        };
+
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for other in collect_occurrences(&modules) {
+            if other.target == occurrence.target {
+                let location = to_location(&modules, other.at)?;
+                changes
+                    .entry(location.uri)
+                    .or_default()
+                    .push(TextEdit::new(location.range, name.to_owned()));
+            }
+        }
+
+        Ok(Some(WorkspaceEdit::new(changes)))
+    }
+}
+
+fn handle<P, R>(
+    request: Request,
+    handler: impl FnOnce(P) -> anyhow::Result<R>,
+) -> anyhow::Result<serde_json::Value>
+where
+    P: serde::de::DeserializeOwned,
+    R: serde::Serialize,
+{
+    let params = serde_json::from_value(request.params)?;
+    Ok(serde_json::to_value(handler(params)?)?)
+}
+
+/// A name within a module, as byte offsets into its source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Name {
+    module: usize,
+    start: usize,
+    end: usize,
+}
+
+impl Name {
+    fn of(modules: &[Module], module: usize, name: &str) -> Self {
+        let source = modules[module].source;
+        let start = (name.as_ptr() as usize).wrapping_sub(source.as_ptr() as usize);
+        Self {
+            module,
+            start,
+            end: start + name.len(),
+        }
+    }
+}
+
+/// A name along with the declaration it refers to. Declarations refer to themselves.
+#[derive(Debug, Clone, Copy)]
+struct Occurrence {
+    at: Name,
+    target: Name,
+}
+
+fn collect_occurrences(modules: &[Module]) -> Vec<Occurrence> {
+    let mut occurrences = Vec::new();
+
+    for (index, module) in modules.iter().enumerate() {
+        let schema = &module.schema;
+
+        for (import, dependency) in schema.imports.iter().zip(&module.dependencies) {
+            // names of wildcard imports are not part of the source
+            if import.is_wildcard {
+                continue;
+            }
+            for name in import.names.iter() {
+                if let Some(target) = find_declaration(modules, *dependency, name) {
+                    occurrences.push(Occurrence {
+                        at: Name::of(modules, index, name),
+                        target,
+                    });
+                }
+            }
+        }
+
+        for (name, model) in schema.models.iter() {
+            let declaration = Name::of(modules, index, name);
+            occurrences.push(Occurrence {
+                at: declaration,
+                target: declaration,
+            });
+
+            let parameters = model.type_parameters();
+            for parameter in parameters {
+                let declaration = Name::of(modules, index, parameter);
+                occurrences.push(Occurrence {
+                    at: declaration,
+                    target: declaration,
+                });
+            }
+
+            let mut shapes = Vec::new();
+            match model {
+                Model::Struct(inner) => shapes.extend(fields(&inner.fields)),
+                Model::Enum(_) => {}
+                Model::Union(inner) => {
+                    for (_, variant) in inner.variants.iter() {
+                        match variant {
+                            Variant::Unit => {}
+                            Variant::Tuple(shape) => shapes.push(shape),
+                            Variant::Struct(inner) => shapes.extend(fields(inner)),
+                        }
+                    }
+                }
+                Model::Alias(inner) => shapes.push(&inner.shape.inner),
+                Model::External(inner) => shapes.push(&inner.shape.inner),
+            }
+
+            for shape in shapes {
+                collect_references(modules, index, shape, parameters, &mut occurrences);
+            }
+        }
+
+        for (_, service) in schema.services.iter() {
+            for (_, method) in service.methods.iter() {
+                for (_, shape) in method.inner.inputs.iter() {
+                    collect_references(modules, index, shape, &[], &mut occurrences);
+                }
+                if let Some(ref shape) = method.inner.output {
+                    collect_references(modules, index, shape, &[], &mut occurrences);
+                }
+            }
+        }
+    }
+
+    occurrences
+}
+
+fn fields<'m, 'a>(
+    fields: &'m OrderedHashMap<&'a str, Annotated<'a, Shape<'a>>>,
+) -> impl Iterator<Item = &'m Shape<'a>> {
+    fields.iter().map(|(_, shape)| &shape.inner)
+}
+
+fn collect_references(
+    modules: &[Module],
+    module: usize,
+    shape: &Shape,
+    parameters: &[&str],
+    occurrences: &mut Vec<Occurrence>,
+) {
+    match shape {
+        Shape::Nullable(inner) => {
+            collect_references(modules, module, inner, parameters, occurrences)
+        }
+        Shape::List(inner) => collect_references(modules, module, inner, parameters, occurrences),
+        Shape::Map(key, value) => {
+            collect_references(modules, module, key, parameters, occurrences);
+            collect_references(modules, module, value, parameters, occurrences);
+        }
+        Shape::Reference(name, arguments) => {
+            let target = match parameters.iter().find(|parameter| *parameter == name) {
+                Some(parameter) => Some(Name::of(modules, module, parameter)),
+                None => resolve(modules, module, name),
+            };
+            if let Some(target) = target {
+                occurrences.push(Occurrence {
+                    at: Name::of(modules, module, name),
+                    target,
+                });
+            }
+
+            for argument in arguments {
+                collect_references(modules, module, argument, parameters, occurrences);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Finds the declaration of a model visible from within a module
+fn resolve(modules: &[Module], module: usize, name: &str) -> Option<Name> {
+    if let Some(declaration) = find_declaration(modules, module, name) {
+        return Some(declaration);
+    }
+
+    let module = &modules[module];
+    module
+        .schema
+        .imports
+        .iter()
+        .zip(&module.dependencies)
+        .find(|(import, _)| import.names.contains(&name))
+        .and_then(|(_, dependency)| find_declaration(modules, *dependency, name))
+}
+
+fn find_declaration(modules: &[Module], module: usize, name: &str) -> Option<Name> {
+    modules[module]
+        .schema
+        .models
+        .iter()
+        .find(|(declared, _)| **declared == name)
+        .map(|(declared, _)| Name::of(modules, module, declared))
+}
+
+fn find_model<'m, 'a>(module: &'m Module<'a>, start: usize) -> Option<&'m Model<'a>> {
+    module
+        .schema
+        .models
+        .iter()
+        .find(|(name, _)| name.as_ptr() as usize == module.source.as_ptr() as usize + start)
+        .map(|(_, model)| model)
+}
+
+/// Follows references to aliases without type parameters
+fn resolve_shape<'a>(modules: &[Module<'a>], module: usize, shape: &Shape<'a>, depth: usize) -> Shape<'a> {
+    // recursive aliases are reported by the checker
+    if depth > 64 {
+        return shape.clone();
+    }
+
+    let resolve_inner = |inner: &Shape<'a>| Box::new(resolve_shape(modules, module, inner, depth + 1));
+
+    match shape {
+        Shape::Nullable(inner) => Shape::Nullable(resolve_inner(inner)),
+        Shape::List(inner) => Shape::List(Box::new(inner.map(|inner| *resolve_inner(inner)))),
+        Shape::Map(key, value) => Shape::Map(
+            Box::new(key.map(|key| *resolve_inner(key))),
+            Box::new(value.map(|value| *resolve_inner(value))),
+        ),
+        Shape::Reference(name, arguments) if arguments.is_empty() => {
+            let Some(declaration) = resolve(modules, module, name) else {
+                return shape.clone();
+            };
+            match find_model(&modules[declaration.module], declaration.start) {
+                Some(Model::Alias(alias)) if alias.type_parameters.is_empty() => {
+                    resolve_shape(modules, declaration.module, &alias.shape.inner, depth + 1)
+                }
+                _ => shape.clone(),
+            }
+        }
+        _ => shape.clone(),
+    }
+}
+
+fn find_occurrence(
+    modules: &[Module],
+    position: &TextDocumentPositionParams,
+) -> Option<Occurrence> {
+    let path = to_path(&position.text_document.uri);
+    let index = modules.iter().position(|module| module.path == path)?;
+    let offset = to_offset(modules[index].source, position.position);
+
+    collect_occurrences(modules).into_iter().find(|occurrence| {
+        occurrence.at.module == index
+            && occurrence.at.start <= offset
+            && offset <= occurrence.at.end
+    })
+}
+
+fn to_path(uri: &Url) -> PathBuf {
+    let path = uri
+        .to_file_path()
+        .unwrap_or_else(|_| PathBuf::from(uri.path()));
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+fn to_location(modules: &[Module], name: Name) -> anyhow::Result<Location> {
+    let uri = Url::from_file_path(modules[name.module].path)
+        .map_err(|_| anyhow!("invalid path {}", modules[name.module].path.display()))?;
+    Ok(Location::new(uri, to_range(modules, name)))
+}
+
+fn to_range(modules: &[Module], name: Name) -> Range {
+    let source = modules[name.module].source;
+    Range::new(
+        to_position(source, name.start),
+        to_position(source, name.end),
+    )
+}
+
+/// Converts a byte offset into a position counting UTF-16 code units
+fn to_position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let character = before[line_start..].encode_utf16().count();
+
+    Position::new(line as u32, character as u32)
+}
+
+fn to_offset(source: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match source[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return source.len(),
+        }
+    }
+
+    let mut units = 0;
+    for (i, c) in source[line_start..].char_indices() {
+        if units >= position.character as usize || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+
+    source.len()
+}
+
+fn from_diagnostic(source: &str, position: diagnostic::Position) -> Position {
+    let line = source.lines().nth(position.line - 1).unwrap_or_default();
+    let character = line
+        .chars()
+        .take(position.column - 1)
+        .map(char::len_utf16)
+        .sum::<usize>();
+
+    Position::new(position.line as u32 - 1, character as u32)
+}
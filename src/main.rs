@@ -1,76 +1,641 @@
-use clap::Parser;
-use console::style;
-use lang::schema::Schema;
-use std::{fmt::Display, fs, path::Path, time::Instant};
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use config::Config;
+use console::{style, Color};
+use hgen::{
+    emit,
+    lang::{
+        diagnostic::Diagnostic,
+        diff::{self, Policy},
+        format,
+        loader::{find_schemas, Module, Sources},
+    },
+};
+use serde_json::{Map, Value};
+use similar::TextDiff;
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
-mod emit;
-mod lang;
+mod config;
+mod lsp;
+mod watch;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    options: Options,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate every target of a project configuration
+    Build {
+        /// Path to the project configuration
+        #[clap(short, long, default_value = config::FILE_NAME)]
+        config: PathBuf,
+
+        /// Verify that generated files are up to date instead of writing them
+        #[clap(long)]
+        check: bool,
+
+        /// Regenerate whenever an input schema or the configuration changes
+        #[clap(long, conflicts_with = "check")]
+        watch: bool,
+    },
+    /// Compare two versions of a schema and report breaking changes
+    Diff {
+        /// Path to the previous version of the schema
+        old: PathBuf,
+
+        /// Path to the next version of the schema
+        new: PathBuf,
+
+        /// Which kind of compatibility to keep, either `wire` or `source`
+        #[clap(long, default_value = "wire")]
+        policy: Policy,
+
+        /// Print the changes as JSON instead of a report
+        #[clap(long)]
+        json: bool,
+    },
+    /// Start a language server on stdio
+    Lsp,
+    /// Format schema files in place
+    Fmt {
+        /// Schema files, or directories to search for schema files
+        #[clap(required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Verify that schema files are formatted instead of rewriting them
+        #[clap(long)]
+        check: bool,
+    },
+}
+
+#[derive(clap::Args)]
 struct Options {
     /// Path to the input schema file
-    #[clap(short, long)]
-    input: String,
+    #[clap(short, long, required = true)]
+    input: Option<String>,
+
+    /// Path to the output file, or the output directory when using a plugin
+    #[clap(short, long, required = true)]
+    output: Option<String>,
+
+    /// Name or path of an external generator to emit code with
+    #[clap(long)]
+    plugin: Option<String>,
 
-    /// Path to the output file
-    #[clap(short, long)]
-    output: String,
+    /// Option passed on to the emitter as `key=value`, where the value is
+    /// read as TOML if possible, e.g. `package=com.example.api`
+    #[clap(long = "option", value_name = "KEY=VALUE")]
+    option: Vec<String>,
+
+    /// Verify that generated files are up to date instead of writing them
+    #[clap(long)]
+    check: bool,
+
+    /// Regenerate whenever the input schema or one of its imports changes
+    #[clap(long, conflicts_with = "check")]
+    watch: bool,
 }
 
 fn main() -> anyhow::Result<()> {
-    let options = Options::parse();
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Command::Build {
+            config,
+            check,
+            watch,
+        }) => build(&config, check, watch),
+        Some(Command::Diff {
+            old,
+            new,
+            policy,
+            json,
+        }) => diff(&old, &new, policy, json),
+        Some(Command::Lsp) => lsp::run(),
+        Some(Command::Fmt { paths, check }) => fmt(&paths, check),
+        None => generate(cli.options),
+    }
+}
+
+fn generate(options: Options) -> anyhow::Result<()> {
+    if options.watch {
+        return watch::watch(|files| generate_once(&options, files));
+    }
+
+    if !generate_once(&options, &mut Vec::new())? {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Generates code for the input schema, pushing every schema read into
+/// `files`. Returns whether the schema was valid.
+fn generate_once(options: &Options, files: &mut Vec<PathBuf>) -> anyhow::Result<bool> {
     let started = Instant::now();
 
-    let input_path = Path::new(&options.input).to_path_buf();
-    let input_file_name = input_path.file_stem().unwrap();
+    let input_path = PathBuf::from(options.input.as_deref().unwrap());
+    let input_file_name = schema_name(&input_path)?;
 
-    let output_path = Path::new(&options.output).to_path_buf();
-    let output_dir = output_path.parent().unwrap();
-    let output_file_extension = output_path.extension().unwrap().to_str().unwrap();
+    let output_path = PathBuf::from(options.output.as_deref().unwrap());
 
-    let strategy = Strategy::parse(output_file_extension).expect("Unsupported output");
+    let strategy = match &options.plugin {
+        Some(plugin) => Strategy::Plugin(plugin.clone()),
+        None => Strategy::from_path(&output_path).with_context(|| {
+            format!(
+                "unsupported output {}, pass a known extension or a plugin",
+                output_path.display()
+            )
+        })?,
+    };
+    let emitter_options = parse_options(&options.option)?;
 
     println!("parsing schema");
     println!("{}", style(input_path.display()).dim());
 
-    let source = fs::read_to_string(&input_path)?;
-    let schema = Schema::parse(&source);
-    println!("{:?}", schema);
-    let output = strategy.emit(input_file_name.to_str().unwrap(), &schema);
+    files.push(input_path.clone());
+    let sources = Sources::load(&input_path)?;
+    files.extend(sources.files.iter().map(|file| file.path.clone()));
+
+    let Some(modules) = compile(&sources) else {
+        return Ok(false);
+    };
+
+    let diagnostics = strategy.check(&modules);
+    if !diagnostics.is_empty() {
+        eprint!("{}", sources.render(&diagnostics));
+        return Ok(false);
+    }
 
     println!("emitting {} code", style(&strategy).cyan().bold());
-    println!("{}", style(output_path.display()).dim());
 
-    fs::create_dir_all(output_dir).unwrap();
-    fs::write(&output_path, output).unwrap();
+    // imported schemas are emitted next to the output, mirroring their
+    // directories relative to the input schema
+    let layout = Layout {
+        source: modules[0].path.parent().unwrap(),
+        output: output_path.parent().unwrap_or(Path::new("")),
+    };
+    let files = strategy.emit(
+        &output_path,
+        &layout,
+        input_file_name,
+        &modules,
+        &emitter_options,
+    )?;
+
+    let mut summary = Summary::default();
+    for (path, content) in files {
+        summary.output(&path, &content, options.check)?;
+    }
+
+    summary.finish(options.check, started);
+
+    Ok(true)
+}
+
+/// Name of the schema at `path`, which generated code is named after
+fn schema_name(path: &Path) -> anyhow::Result<&str> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .with_context(|| format!("{} is not a valid schema file name", path.display()))
+}
+
+/// Reads `key=value` options into the same form as the options of targets in
+/// the project configuration. Values which are not valid TOML are strings.
+fn parse_options(options: &[String]) -> anyhow::Result<Map<String, Value>> {
+    let mut lines = Vec::new();
+    for option in options {
+        let Some((key, value)) = option.split_once('=') else {
+            anyhow::bail!("option `{}` is not of the form key=value", option);
+        };
+        let line = format!("{} = {}", key.trim(), value.trim());
+        match toml::from_str::<toml::Table>(&line) {
+            Ok(_) => lines.push(line),
+            Err(_) => lines.push(format!("{} = {}", key.trim(), toml::Value::from(value.trim()))),
+        }
+    }
+
+    Ok(toml::from_str(&lines.join("\n"))?)
+}
+
+fn build(path: &Path, check: bool, watch: bool) -> anyhow::Result<()> {
+    if watch {
+        return watch::watch(|files| build_once(path, check, files));
+    }
+
+    if !build_once(path, check, &mut Vec::new())? {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Generates every target of the configuration, pushing the configuration
+/// and every schema read into `files`. Returns whether all schemas were valid.
+fn build_once(path: &Path, check: bool, files: &mut Vec<PathBuf>) -> anyhow::Result<bool> {
+    let started = Instant::now();
+
+    files.push(path.to_path_buf());
+    let config = Config::load(path)?;
+    let strategies = config
+        .targets
+        .iter()
+        .map(|target| target.strategy())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // the output of every target mirrors the directories of the schemas
+    // below the directory containing all inputs
+    let inputs = config
+        .inputs
+        .iter()
+        .map(|input| {
+            fs::canonicalize(input).with_context(|| format!("failed to open {}", input.display()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let root = common_directory(&inputs);
+
+    let mut summary = Summary::default();
+    let mut emitted = HashSet::new();
+    let mut failed = false;
+
+    for input_path in config.inputs.iter() {
+        println!("parsing {}", style(input_path.display()).dim());
+
+        files.push(input_path.clone());
+        let sources = Sources::load(input_path)?;
+        files.extend(sources.files.iter().map(|file| file.path.clone()));
+
+        let Some(modules) = compile(&sources) else {
+            failed = true;
+            continue;
+        };
+
+        let name = schema_name(input_path)?;
+
+        for (target, strategy) in config.targets.iter().zip(&strategies) {
+            let diagnostics = strategy.check(&modules);
+            if !diagnostics.is_empty() {
+                eprint!("{}", sources.render(&diagnostics));
+                failed = true;
+                continue;
+            }
+
+            let layout = Layout {
+                source: &root,
+                output: &target.output,
+            };
+            let output_path = match strategy.extension() {
+                Some(extension) => layout.place(modules[0].path, extension)?.0,
+                None => target.output.clone(),
+            };
+
+            let files = strategy.emit(&output_path, &layout, name, &modules, &target.options)?;
+            for (path, content) in files {
+                // schemas imported by several inputs are only written once
+                if emitted.insert(path.clone()) {
+                    summary.output(&path, &content, check)?;
+                }
+            }
+        }
+    }
+
+    if failed {
+        return Ok(false);
+    }
+
+    summary.finish(check, started);
+
+    Ok(true)
+}
+
+/// Returns the deepest directory containing all of the given files
+fn common_directory(paths: &[PathBuf]) -> PathBuf {
+    let mut directory = paths
+        .first()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    for path in paths {
+        while !path.starts_with(&directory) {
+            directory.pop();
+        }
+    }
+    directory
+}
+
+fn diff(old: &Path, new: &Path, policy: Policy, json: bool) -> anyhow::Result<()> {
+    let old_sources = Sources::load(old)?;
+    let new_sources = Sources::load(new)?;
+    let (Some(old_modules), Some(new_modules)) = (compile(&old_sources), compile(&new_sources))
+    else {
+        std::process::exit(1);
+    };
+
+    let changes = diff::diff_modules(&old_modules, &new_modules);
+    let breaking = changes
+        .iter()
+        .filter(|change| change.kind.is_breaking(policy))
+        .count();
+
+    if json {
+        let changes = changes
+            .iter()
+            .map(|change| {
+                serde_json::json!({
+                    "kind": change.kind.id(),
+                    "path": change.path,
+                    "message": change.message,
+                    "breaking": change.kind.is_breaking(policy),
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+    } else {
+        for change in changes.iter() {
+            let label = match change.kind.is_breaking(policy) {
+                true => style("breaking  ").red(),
+                false => style("compatible").green(),
+            };
+            println!("{} {}", label, change.message);
+        }
+
+        println!(
+            "{} change(s), {} breaking under the {} policy",
+            changes.len(),
+            breaking,
+            match policy {
+                Policy::Wire => "wire",
+                Policy::Source => "source",
+            }
+        );
+    }
+
+    if breaking > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn fmt(paths: &[PathBuf], check: bool) -> anyhow::Result<()> {
+    let started = Instant::now();
+
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            find_schemas(path, &mut files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    let mut summary = Summary::default();
+    let mut failed = false;
 
-    println!("done in {}μs", started.elapsed().as_micros());
+    for path in files {
+        let source = fs::read_to_string(&path)?;
+        match format::format_schema(&source) {
+            Ok(formatted) => summary.output(&path, &formatted, check)?,
+            Err(diagnostics) => {
+                for diagnostic in diagnostics {
+                    eprintln!("{}", diagnostic.with_file(&path).render(&source));
+                }
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+
+    summary.finish(check, started);
 
     Ok(())
 }
 
+/// Parses and checks all loaded sources, reporting any diagnostics
+fn compile(sources: &Sources) -> Option<Vec<Module<'_>>> {
+    match sources.compile() {
+        Ok(modules) => Some(modules),
+        Err(diagnostics) => {
+            eprint!("{}", sources.render(&diagnostics));
+            None
+        }
+    }
+}
+
+/// Counts the emitted files which differ from the files on disk
+#[derive(Default)]
+struct Summary {
+    changed: usize,
+    unchanged: usize,
+}
+
+impl Summary {
+    /// Writes a file unless it already has the given content. When checking,
+    /// prints a diff against the file on disk instead.
+    fn output(&mut self, path: &Path, content: &str, check: bool) -> anyhow::Result<()> {
+        let existing = fs::read_to_string(path).ok();
+        let (label, color) = match existing.as_deref() {
+            Some(existing) if existing == content => {
+                self.unchanged += 1;
+                println!(
+                    "{} {}",
+                    style("unchanged").dim(),
+                    style(path.display()).dim()
+                );
+                return Ok(());
+            }
+            Some(_) if check => ("stale    ", Color::Red),
+            None if check => ("missing  ", Color::Red),
+            Some(_) => ("updated  ", Color::Yellow),
+            None => ("created  ", Color::Green),
+        };
+
+        self.changed += 1;
+        println!("{} {}", style(label).fg(color), style(path.display()).dim());
+
+        if check {
+            let path = path.display().to_string();
+            let diff = TextDiff::from_lines(existing.as_deref().unwrap_or_default(), content);
+            print!("{}", diff.unified_diff().header(&path, &path));
+        } else {
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, content)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the summary, exiting with an error if a checked file is out of date
+    fn finish(&self, check: bool, started: Instant) {
+        let elapsed = started.elapsed().as_micros();
+
+        if !check {
+            println!(
+                "{} changed, {} unchanged in {}μs",
+                self.changed, self.unchanged, elapsed
+            );
+        } else if self.changed > 0 {
+            eprintln!(
+                "{} out of date, {} up to date in {}μs",
+                self.changed, self.unchanged, elapsed
+            );
+            std::process::exit(1);
+        } else {
+            println!("{} up to date in {}μs", self.unchanged, elapsed);
+        }
+    }
+}
+
+/// Mirrors the directories of schemas below `source` as directories of
+/// generated files below `output`
+struct Layout<'a> {
+    source: &'a Path,
+    output: &'a Path,
+}
+
+impl Layout<'_> {
+    /// Returns the path of the file generated for a schema, along with how
+    /// many directories below `output` it is
+    fn place(&self, schema: &Path, extension: &str) -> anyhow::Result<(PathBuf, usize)> {
+        let Ok(relative) = schema.strip_prefix(self.source) else {
+            anyhow::bail!(
+                "{} is imported from outside of {}, which the output cannot mirror",
+                schema.display(),
+                self.source.display()
+            );
+        };
+        let depth = relative.components().count() - 1;
+        Ok((self.output.join(relative).with_extension(extension), depth))
+    }
+}
+
 #[derive(Debug, Clone)]
 enum Strategy {
-    JSON,
+    Json,
     Rust,
     TypeScript,
     Dart,
+    GraphQl,
+    JsonSchema,
+    OpenApi(emit::openapi::Format),
+    Proto,
+    Python,
+    Plugin(String),
 }
 
 impl Strategy {
-    fn emit(&self, name: &str, schema: &Schema) -> String {
+    /// Reports problems with the modules which only matter to this strategy
+    fn check(&self, modules: &[Module]) -> Vec<Diagnostic> {
         match self {
-            Strategy::Rust => emit::rs::emit_schema(name, &schema),
-            Strategy::TypeScript => emit::ts::emit_schema(name, &schema),
-            Strategy::Dart => emit::dart::emit_schema(name, &schema),
-            _ => panic!("unsupported strategy: {:?}", self),
+            Strategy::Proto => emit::proto::check(modules),
+            Strategy::OpenApi(_) => emit::openapi::check(modules),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Emits code for all modules, returning the files to write. Strategies
+    /// emitting one file per module write the entry module to `output` and
+    /// the others where `layout` places them, along with the runtime of
+    /// services. Other built-in strategies emit a single file at `output`,
+    /// while plugins may emit any number of files into the `output` directory.
+    fn emit(
+        &self,
+        output: &Path,
+        layout: &Layout,
+        name: &str,
+        modules: &[Module],
+        options: &Map<String, Value>,
+    ) -> anyhow::Result<Vec<(PathBuf, String)>> {
+        let content = match self {
+            Strategy::GraphQl => emit::graphql::emit_schema(modules, options),
+            Strategy::JsonSchema => emit::jsonschema::emit_schema(name, modules, options),
+            Strategy::OpenApi(format) => emit::openapi::emit_schema(name, modules, options, *format),
+            Strategy::Proto => emit::proto::emit_schema(modules, options),
+            Strategy::Plugin(plugin) => {
+                return Ok(emit::plugin::emit_schema(plugin, name, modules, options)?
+                    .into_iter()
+                    .map(|(path, content)| (output.join(path), content))
+                    .collect());
+            }
+            _ => return self.emit_modules(output, layout, name, modules, options),
+        };
+
+        Ok(vec![(output.to_path_buf(), content)])
+    }
+
+    fn emit_modules(
+        &self,
+        output: &Path,
+        layout: &Layout,
+        name: &str,
+        modules: &[Module],
+        options: &Map<String, Value>,
+    ) -> anyhow::Result<Vec<(PathBuf, String)>> {
+        let extension = self.extension().unwrap();
+        let mut files = Vec::new();
+
+        for (i, module) in modules.iter().enumerate() {
+            let (path, depth) = layout.place(module.path, extension)?;
+            let (path, name) = match i {
+                0 => (output.to_path_buf(), name),
+                _ => (path, module.path.file_stem().unwrap().to_str().unwrap()),
+            };
+
+            let schema = &module.schema;
+            let runtime = emit::rpc::path(depth);
+            let content = match self {
+                Strategy::Rust => emit::rs::emit_schema(name, schema, &runtime),
+                Strategy::TypeScript => emit::ts::emit_schema(name, schema, &runtime),
+                Strategy::Dart => emit::dart::emit_schema(name, schema, &runtime),
+                Strategy::Json => emit::json::emit_schema(name, schema),
+                Strategy::Python => emit::py::emit_schema(name, schema, &runtime, options),
+                _ => unreachable!("{} does not emit one file per module", self),
+            };
+            files.push((path, content));
         }
+
+        if modules.iter().any(|module| !module.schema.services.is_empty()) {
+            let runtime = match self {
+                Strategy::Rust => Some(emit::rs::emit_runtime()),
+                Strategy::TypeScript => Some(emit::ts::emit_runtime()),
+                Strategy::Dart => Some(emit::dart::emit_runtime()),
+                Strategy::Python => Some(emit::py::emit_runtime()),
+                _ => None,
+            };
+            if let Some(runtime) = runtime {
+                let file_name = format!("{}.{}", emit::rpc::MODULE, extension);
+                files.push((layout.output.join(file_name), runtime));
+            }
+        }
+
+        Ok(files)
     }
 
-    pub fn parse(value: &str) -> Option<Self> {
-        return Self::from_extension(value);
+    /// Picks the strategy from the file name of `path`, preferring the longest
+    /// extension so that `api.openapi.json` is not emitted as plain JSON
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        file_name
+            .match_indices('.')
+            .find_map(|(index, _)| Self::from_extension(&file_name[index + 1..]))
     }
 
     pub fn from_extension(extension: &str) -> Option<Self> {
@@ -78,11 +643,51 @@ impl Strategy {
             "rs" => Strategy::Rust,
             "ts" => Strategy::TypeScript,
             "dart" => Strategy::Dart,
-            "json" => Strategy::JSON,
+            "json" => Strategy::Json,
+            "graphql" => Strategy::GraphQl,
+            "schema.json" => Strategy::JsonSchema,
+            "openapi.json" => Strategy::OpenApi(emit::openapi::Format::Json),
+            "openapi.yaml" | "openapi.yml" => Strategy::OpenApi(emit::openapi::Format::Yaml),
+            "proto" => Strategy::Proto,
+            "py" => Strategy::Python,
             _ => return None,
         }
         .into()
     }
+
+    pub fn from_language(language: &str) -> Option<Self> {
+        match language {
+            "rust" => Strategy::Rust,
+            "typescript" => Strategy::TypeScript,
+            "dart" => Strategy::Dart,
+            "json" => Strategy::Json,
+            "graphql" => Strategy::GraphQl,
+            "jsonschema" => Strategy::JsonSchema,
+            "openapi" => Strategy::OpenApi(emit::openapi::Format::Json),
+            "openapi-yaml" => Strategy::OpenApi(emit::openapi::Format::Yaml),
+            "proto" => Strategy::Proto,
+            "python" => Strategy::Python,
+            _ => return None,
+        }
+        .into()
+    }
+
+    /// File extension of the single file emitted by built-in strategies
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Strategy::Rust => Some("rs"),
+            Strategy::TypeScript => Some("ts"),
+            Strategy::Dart => Some("dart"),
+            Strategy::Json => Some("json"),
+            Strategy::GraphQl => Some("graphql"),
+            Strategy::JsonSchema => Some("schema.json"),
+            Strategy::OpenApi(emit::openapi::Format::Json) => Some("openapi.json"),
+            Strategy::OpenApi(emit::openapi::Format::Yaml) => Some("openapi.yaml"),
+            Strategy::Proto => Some("proto"),
+            Strategy::Python => Some("py"),
+            Strategy::Plugin(_) => None,
+        }
+    }
 }
 
 impl Display for Strategy {
@@ -91,7 +696,13 @@ impl Display for Strategy {
             Strategy::Rust => write!(f, "Rust"),
             Strategy::TypeScript => write!(f, "TypeScript"),
             Strategy::Dart => write!(f, "Dart"),
-            Strategy::JSON => write!(f, "JSON"),
+            Strategy::Json => write!(f, "JSON"),
+            Strategy::GraphQl => write!(f, "GraphQL"),
+            Strategy::JsonSchema => write!(f, "JSON Schema"),
+            Strategy::OpenApi(_) => write!(f, "OpenAPI"),
+            Strategy::Proto => write!(f, "Protocol Buffers"),
+            Strategy::Python => write!(f, "Python"),
+            Strategy::Plugin(plugin) => write!(f, "{}", plugin),
         }
     }
 }
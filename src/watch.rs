@@ -0,0 +1,78 @@
+use console::style;
+use notify::{RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::PathBuf,
+    sync::mpsc,
+    time::Duration,
+};
+
+/// How long to wait for further changes before regenerating, so that a burst
+/// of saves only triggers a single run
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Runs `run` whenever one of the files it reported has changed. Each run
+/// pushes the files it read into the given list and returns whether it
+/// succeeded. Errors are printed instead of stopping the watcher.
+pub fn watch(mut run: impl FnMut(&mut Vec<PathBuf>) -> anyhow::Result<bool>) -> anyhow::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+
+    let mut files = BTreeSet::new();
+    let mut directories = BTreeSet::new();
+
+    loop {
+        let mut read = Vec::new();
+        let succeeded = match run(&mut read) {
+            Ok(succeeded) => succeeded,
+            Err(error) => {
+                eprintln!("{} {:#}", style("error:").red().bold(), error);
+                false
+            }
+        };
+
+        // a failed run may not have reached every import, so keep watching
+        // the files of previous runs until the schema is valid again
+        if succeeded {
+            files.clear();
+        }
+        files.extend(read.into_iter().map(|path| {
+            fs::canonicalize(&path)
+                .or_else(|_| std::path::absolute(&path))
+                .unwrap_or(path)
+        }));
+
+        // editors often save by replacing the file, which would end a watch
+        // on the file itself, so the containing directories are watched
+        let mut next = files
+            .iter()
+            .filter_map(|file| file.parent().map(|parent| parent.to_path_buf()))
+            .collect::<BTreeSet<_>>();
+        for directory in directories.difference(&next) {
+            let _ = watcher.unwatch(directory);
+        }
+        let added = next.difference(&directories).cloned().collect::<Vec<_>>();
+        for directory in added {
+            // missing directories are retried after the next change
+            if watcher.watch(&directory, RecursiveMode::NonRecursive).is_err() {
+                next.remove(&directory);
+            }
+        }
+        directories = next;
+
+        println!(
+            "{}",
+            style(format!("watching {} file(s) for changes", files.len())).dim()
+        );
+
+        // wait for a change to one of the files, then for the burst to settle
+        loop {
+            let event = receiver.recv()??;
+            if !event.kind.is_access() && event.paths.iter().any(|path| files.contains(path)) {
+                break;
+            }
+        }
+        while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+    }
+}